@@ -0,0 +1,128 @@
+//! The separately chained layout `Table` had before it moved to control
+//! bytes, kept as a baseline for the benchmarks.
+//!
+//! Only `insert` and `get` are kept, with the same buckets, load factor and
+//! incremental rehashing.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+type Bucket<K, V> = Vec<(K, V)>;
+
+/// Load factor the table grows at.
+const MAX_LOAD_FACTOR: f64 = 0.75;
+/// Bucket count used when inserting into a table created with no buckets.
+const MIN_BUCKETS: usize = 8;
+/// Buckets migrated by every insert while a rehash is in progress.
+const REHASH_STEP: usize = 4;
+
+/// A hash table resolving collisions by separate chaining.
+pub struct ChainedTable<K, V> {
+    elements: Vec<Bucket<K, V>>,
+    /// Buckets being migrated away from, every one before `next` empty.
+    old: Vec<Bucket<K, V>>,
+    next: usize,
+    len: usize,
+    hash_builder: RandomState,
+}
+
+/// Map a hash to a bucket index.
+fn bucket_index(hash: u64, buckets: usize) -> usize {
+    (hash as usize) % buckets
+}
+
+/// Find the value for a key in a bucket.
+fn lookup<'a, K: Eq, V>(bucket: &'a [(K, V)], key: &K) -> Option<&'a V> {
+    bucket.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+impl<K, V> ChainedTable<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create a new, empty `ChainedTable`.
+    pub fn new() -> Self {
+        Self {
+            elements: Vec::new(),
+            old: Vec::new(),
+            next: 0,
+            len: 0,
+            hash_builder: RandomState::new(),
+        }
+    }
+    /// Get the value for the given key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        if self.len == 0 {
+            return None;
+        }
+        let hash = self.hash_builder.hash_one(key);
+        if !self.old.is_empty() {
+            let index = bucket_index(hash, self.old.len());
+            if index >= self.next {
+                if let Some(value) = lookup(&self.old[index], key) {
+                    return Some(value);
+                }
+            }
+        }
+        lookup(&self.elements[bucket_index(hash, self.elements.len())], key)
+    }
+    /// Insert a new key-value pair, growing the table first if it would
+    /// cross the load factor.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.rehash_step();
+        let hash = self.hash_builder.hash_one(&key);
+        if self.len > 0 {
+            if let Some(slot) = self.find_mut(hash, &key) {
+                return Some(mem::replace(slot, value));
+            }
+        }
+        if (self.len + 1) as f64 > self.elements.len() as f64 * MAX_LOAD_FACTOR {
+            let buckets = (self.elements.len() * 2)
+                .max(((self.len + 1) as f64 / MAX_LOAD_FACTOR).ceil() as usize)
+                .max(MIN_BUCKETS);
+            while !self.old.is_empty() {
+                self.rehash_step();
+            }
+            let empty = std::iter::repeat_with(Vec::new).take(buckets).collect();
+            self.old = mem::replace(&mut self.elements, empty);
+            self.next = 0;
+        }
+        let index = bucket_index(hash, self.elements.len());
+        self.elements[index].push((key, value));
+        self.len += 1;
+        None
+    }
+    /// Find the value for a key with the given hash, mutably.
+    fn find_mut(&mut self, hash: u64, key: &K) -> Option<&mut V> {
+        if !self.old.is_empty() {
+            let index = bucket_index(hash, self.old.len());
+            if index >= self.next {
+                if let Some((_, v)) = self.old[index].iter_mut().find(|(k, _)| k == key) {
+                    return Some(v);
+                }
+            }
+        }
+        let index = bucket_index(hash, self.elements.len());
+        self.elements[index]
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+    /// Migrate the next few buckets of the old array, if a rehash is in progress.
+    fn rehash_step(&mut self) {
+        if self.old.is_empty() {
+            return;
+        }
+        let end = (self.next + REHASH_STEP).min(self.old.len());
+        for bucket in &mut self.old[self.next..end] {
+            for (key, value) in mem::take(bucket) {
+                let index = bucket_index(self.hash_builder.hash_one(&key), self.elements.len());
+                self.elements[index].push((key, value));
+            }
+        }
+        self.next = end;
+        if end == self.old.len() {
+            self.old = Vec::new();
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Compare the read-heavy throughput of `ShardedTable` with a `Table` behind
+//! a single `Mutex`, as the number of threads grows.
+//!
+//! Run with `cargo bench --bench sharded`. Scaling needs as many cores as
+//! threads, so runs on fewer cores only show the locking overhead.
+use rust_ds::hash_table::{ShardedTable, Table};
+use std::hint::black_box;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const KEYS: u64 = 100_000;
+const OPS_PER_THREAD: u64 = 1_000_000;
+/// One operation in this many is a write.
+const WRITE_EVERY: u64 = 10;
+const THREADS: [u64; 4] = [1, 2, 4, 8];
+
+/// Run `op` on `threads` threads at once and return the total time.
+/// `op` gets a key and whether to write it rather than read it.
+fn run<F: Fn(u64, bool) + Sync>(threads: u64, op: F) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for t in 0..threads {
+            let op = &op;
+            scope.spawn(move || {
+                for i in 0..OPS_PER_THREAD {
+                    op((t * OPS_PER_THREAD + i) * 7919 % KEYS, i % WRITE_EVERY == 0);
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+fn report(layout: &str, threads: u64, elapsed: Duration) {
+    let ops = (threads * OPS_PER_THREAD) as f64 / elapsed.as_secs_f64() / 1e6;
+    println!("{layout:<13} {threads} threads {ops:>8.1} Mops/s");
+}
+
+fn main() {
+    println!(
+        "{} available cores",
+        thread::available_parallelism().map_or(1, |n| n.get())
+    );
+    let sharded: ShardedTable<u64, u64> = ShardedTable::default();
+    let mutex = Mutex::new(Table::new(0));
+    for key in 0..KEYS {
+        sharded.insert(key, key);
+        mutex.lock().unwrap().insert(key, key);
+    }
+    for threads in THREADS {
+        let elapsed = run(threads, |key, write| {
+            black_box(match write {
+                true => sharded.insert(key, key).is_some(),
+                false => sharded.get(&key, |v| *v).is_ok(),
+            });
+        });
+        report("ShardedTable", threads, elapsed);
+        let elapsed = run(threads, |key, write| {
+            let mut table = mutex.lock().unwrap();
+            black_box(match write {
+                true => table.insert(key, key).is_some(),
+                false => table.get(&key).is_ok(),
+            });
+        });
+        report("Mutex<Table>", threads, elapsed);
+    }
+}
@@ -0,0 +1,115 @@
+//! Compare the control-byte layout of `Table` with the separately chained
+//! layout it replaced, the `Vec<Option<_>>` slots of `OpenTable` and
+//! `std::collections::HashMap`.
+//!
+//! Run with `cargo bench --bench table`.
+mod chained;
+
+use chained::ChainedTable;
+use rust_ds::hash_table::{OpenTable, Table};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const KEYS: u64 = 200_000;
+const ROUNDS: u32 = 5;
+
+/// Run `f` a few times and return the fastest run.
+fn fastest<F: FnMut()>(mut f: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn report(layout: &str, insert: Duration, hit: Duration, miss: Duration) {
+    let per_key = |d: Duration| d.as_nanos() as f64 / KEYS as f64;
+    println!(
+        "{layout:<10} insert {:>7.1} ns/key   hit {:>7.1} ns/key   miss {:>7.1} ns/key",
+        per_key(insert),
+        per_key(hit),
+        per_key(miss)
+    );
+}
+
+fn main() {
+    let mut table = Table::new(0);
+    let insert = fastest(|| {
+        table = Table::new(0);
+        for key in 0..KEYS {
+            table.insert(key, key);
+        }
+    });
+    let hit = fastest(|| {
+        for key in 0..KEYS {
+            black_box(table.get(&key).is_ok());
+        }
+    });
+    let miss = fastest(|| {
+        for key in KEYS..2 * KEYS {
+            black_box(table.get(&key).is_ok());
+        }
+    });
+    report("Table", insert, hit, miss);
+
+    let mut chained = ChainedTable::new();
+    let insert = fastest(|| {
+        chained = ChainedTable::new();
+        for key in 0..KEYS {
+            chained.insert(key, key);
+        }
+    });
+    let hit = fastest(|| {
+        for key in 0..KEYS {
+            black_box(chained.get(&key).is_some());
+        }
+    });
+    let miss = fastest(|| {
+        for key in KEYS..2 * KEYS {
+            black_box(chained.get(&key).is_some());
+        }
+    });
+    report("Chained", insert, hit, miss);
+
+    let mut open = OpenTable::new(0);
+    let insert = fastest(|| {
+        open = OpenTable::new(0);
+        for key in 0..KEYS {
+            open.insert(key, key);
+        }
+    });
+    let hit = fastest(|| {
+        for key in 0..KEYS {
+            black_box(open.get(&key).is_ok());
+        }
+    });
+    let miss = fastest(|| {
+        for key in KEYS..2 * KEYS {
+            black_box(open.get(&key).is_ok());
+        }
+    });
+    report("OpenTable", insert, hit, miss);
+
+    let mut std = HashMap::new();
+    let insert = fastest(|| {
+        std = HashMap::new();
+        for key in 0..KEYS {
+            std.insert(key, key);
+        }
+    });
+    let hit = fastest(|| {
+        for key in 0..KEYS {
+            black_box(std.contains_key(&key));
+        }
+    });
+    let miss = fastest(|| {
+        for key in KEYS..2 * KEYS {
+            black_box(std.contains_key(&key));
+        }
+    });
+    report("HashMap", insert, hit, miss);
+}
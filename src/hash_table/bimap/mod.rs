@@ -0,0 +1,282 @@
+use super::{Error, Result, Table};
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+
+/// The pairs a `BiMap::insert` replaced to keep the mapping one-to-one.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// No pair was replaced.
+    Neither,
+    /// The same pair was already stored.
+    Pair(L, R),
+    /// The pair holding the same left value was replaced.
+    Left(L, R),
+    /// The pair holding the same right value was replaced.
+    Right(L, R),
+    /// Two pairs were replaced: the one holding the same left value,
+    /// then the one holding the same right value.
+    Both((L, R), (L, R)),
+}
+
+/// `BiMap` is a bidirectional map keeping a one-to-one mapping between
+/// left and right values.
+///
+/// Pairs are stored in two `Table`s, one keyed by each side, so lookups and
+/// removals are O(1) from either side. Each value is cloned once to live in
+/// both tables.
+pub struct BiMap<L, R, S = RandomState> {
+    left: Table<L, R, S>,
+    right: Table<R, L, S>,
+}
+
+impl<L, R> BiMap<L, R, RandomState>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+{
+    /// Create a new `BiMap` with the given number of slots on each side.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            left: Table::new(capacity),
+            right: Table::new(capacity),
+        }
+    }
+}
+
+impl<L, R, S> BiMap<L, R, S>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    /// Create a new, empty `BiMap` that hashes both sides with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            left: Table::with_hasher(hash_builder.clone()),
+            right: Table::with_hasher(hash_builder),
+        }
+    }
+    /// Get a reference to the map's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        self.left.hasher()
+    }
+    /// Insert a pair, removing any pair that shares its left or right value.
+    /// Returns the pairs that were replaced.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let by_left = self.remove_by_left(&left).ok();
+        let by_right = self.remove_by_right(&right).ok();
+        let overwritten = match (by_left, by_right) {
+            (None, None) => Overwritten::Neither,
+            (Some((l, r)), None) if r == right => Overwritten::Pair(l, r),
+            (Some((l, r)), None) => Overwritten::Left(l, r),
+            (None, Some((l, r))) => Overwritten::Right(l, r),
+            (Some(by_left), Some(by_right)) => Overwritten::Both(by_left, by_right),
+        };
+        self.insert_unchecked(left, right);
+        overwritten
+    }
+    /// Insert a pair only if neither of its values is already mapped.
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<()> {
+        if self.contains_left(&left) || self.contains_right(&right) {
+            return Err(Error::KeyExists);
+        }
+        self.insert_unchecked(left, right);
+        Ok(())
+    }
+    /// Store a pair whose values are known to be unmapped.
+    fn insert_unchecked(&mut self, left: L, right: R) {
+        self.left.insert(left.clone(), right.clone());
+        self.right.insert(right, left);
+    }
+    /// Get the right value mapped to the given left value.
+    pub fn get_by_left<Q>(&self, left: &Q) -> Result<&R>
+    where
+        L: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.get(left)
+    }
+    /// Get the left value mapped to the given right value.
+    pub fn get_by_right<Q>(&self, right: &Q) -> Result<&L>
+    where
+        R: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.get(right)
+    }
+    /// Check if the left value is mapped.
+    pub fn contains_left<Q>(&self, left: &Q) -> bool
+    where
+        L: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.contains_key(left)
+    }
+    /// Check if the right value is mapped.
+    pub fn contains_right<Q>(&self, right: &Q) -> bool
+    where
+        R: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.contains_key(right)
+    }
+    /// Remove the pair holding the given left value.
+    pub fn remove_by_left<Q>(&mut self, left: &Q) -> Result<(L, R)>
+    where
+        L: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (left, right) = self.left.remove_entry(left)?;
+        self.right.remove(&right)?;
+        Ok((left, right))
+    }
+    /// Remove the pair holding the given right value.
+    pub fn remove_by_right<Q>(&mut self, right: &Q) -> Result<(L, R)>
+    where
+        R: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (right, left) = self.right.remove_entry(right)?;
+        self.left.remove(&left)?;
+        Ok((left, right))
+    }
+    /// Keep only the pairs for which `f` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&L, &R) -> bool,
+    {
+        let right = &mut self.right;
+        self.left.retain(|l, r| {
+            let keep = f(l, r);
+            if !keep {
+                let _ = right.remove(r);
+            }
+            keep
+        });
+    }
+    /// Iterate over the pairs of the map.
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left.iter()
+    }
+    /// Iterate over the left values of the map.
+    pub fn left_values(&self) -> impl Iterator<Item = &L> {
+        self.left.keys()
+    }
+    /// Iterate over the right values of the map.
+    pub fn right_values(&self) -> impl Iterator<Item = &R> {
+        self.right.keys()
+    }
+    /// Get the number of pairs in the map.
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+    /// Check if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+}
+/// Default implementation for `BiMap`.
+impl<L, R, S> Default for BiMap<L, R, S>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self {
+            left: Table::default(),
+            right: Table::default(),
+        }
+    }
+}
+
+impl<L, R, S> Debug for BiMap<L, R, S>
+where
+    L: Hash + Eq + Clone + Debug,
+    R: Hash + Eq + Clone + Debug,
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<L, R, S> FromIterator<(L, R)> for BiMap<L, R, S>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+    S: BuildHasher + Clone + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = Self::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<L, R, S> Extend<(L, R)> for BiMap<L, R, S>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+    S: BuildHasher + Clone,
+{
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bimap_ops() {
+        let mut names = BiMap::new(8);
+        assert_eq!(names.insert(1, "ada"), Overwritten::Neither);
+        assert_eq!(names.insert(2, "grace"), Overwritten::Neither);
+        assert_eq!(names.get_by_left(&1).unwrap(), &"ada");
+        assert_eq!(names.get_by_right("grace").unwrap(), &2);
+        assert!(names.contains_left(&2));
+        assert!(!names.contains_right("alan"));
+
+        assert_eq!(names.remove_by_left(&1).unwrap(), (1, "ada"));
+        assert!(names.get_by_right("ada").is_err());
+        assert_eq!(names.remove_by_right("grace").unwrap(), (2, "grace"));
+        assert!(names.get_by_left(&2).is_err());
+        assert!(names.is_empty());
+        assert!(names.remove_by_left(&1).is_err());
+    }
+
+    #[test]
+    fn test_bimap_overwrites() {
+        let mut names: BiMap<i32, &str> = [(1, "a"), (2, "b"), (3, "c")].into_iter().collect();
+        assert_eq!(names.insert(1, "a"), Overwritten::Pair(1, "a"));
+        assert_eq!(names.insert(1, "z"), Overwritten::Left(1, "a"));
+        assert_eq!(names.insert(9, "b"), Overwritten::Right(2, "b"));
+        assert_eq!(names.insert(1, "c"), Overwritten::Both((1, "z"), (3, "c")));
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get_by_right("c").unwrap(), &1);
+        assert!(names.get_by_right("z").is_err());
+        assert!(!names.contains_left(&3));
+
+        assert!(matches!(
+            names.insert_no_overwrite(1, "new"),
+            Err(Error::KeyExists)
+        ));
+        assert!(names.insert_no_overwrite(5, "b").is_err());
+        names.insert_no_overwrite(5, "e").unwrap();
+        assert_eq!(names.get_by_left(&5).unwrap(), &"e");
+
+        names.retain(|left, _| *left != 9);
+        assert!(!names.contains_right("b"));
+        let mut rights: Vec<_> = names.right_values().copied().collect();
+        rights.sort();
+        assert_eq!(rights, vec!["c", "e"]);
+        assert_eq!(names.left_values().count(), names.iter().count());
+    }
+}
+// endregion: --- Tests
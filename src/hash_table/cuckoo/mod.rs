@@ -0,0 +1,531 @@
+use super::{Error, Result};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::mem;
+
+/// Evictions tried by an insert before it gives up and uses the stash.
+const MAX_KICKS: usize = 32;
+/// Pairs the stash holds before a failed insert forces a rehash.
+const STASH_SIZE: usize = 4;
+/// Rehashes with fresh seeds tried at one size before the table doubles.
+const MAX_RESEEDS: usize = 4;
+/// Doublings tried by a rehash before the insert that needed it fails.
+const MAX_GROWS: usize = 8;
+/// Bucket count used when inserting into a table created with no buckets.
+const MIN_BUCKETS: usize = 8;
+
+type Slots<K, V> = Vec<Option<(K, V)>>;
+
+/// Where a rehash plans to store a pair.
+#[derive(Clone, Copy)]
+enum Location {
+    Slot(usize, usize),
+    Stash,
+}
+
+/// `CuckooTable` is a hash table using cuckoo hashing.
+///
+/// Pairs live in two arrays, each indexed by its own hash function, and a
+/// key can only be stored in its slot of either array, or in a small stash.
+/// Lookups therefore touch at most two slots plus the stash, whatever the
+/// load. An insert whose slots are taken evicts the resident pair to its
+/// other slot, and so on for a bounded number of kicks; a pair left over goes
+/// to the stash, and once the stash is full the table rehashes with fresh
+/// hash functions, doubling in size if reseeding alone doesn't help. The
+/// stash never grows past its size: if no seed places every key, as with a
+/// hasher that ignores its input, the insert fails and the table is left
+/// as it was.
+///
+/// Both hash functions come from the `BuildHasher` given as `S`, each salted
+/// with its own seed. Seeds are drawn at random for every table and rehash,
+/// so the keys that cycle can't be worked out in advance.
+pub struct CuckooTable<K, V, S = RandomState> {
+    slots: [Slots<K, V>; 2],
+    stash: Vec<(K, V)>,
+    seeds: [u64; 2],
+    len: usize,
+    rehashes: usize,
+    hash_builder: S,
+}
+
+/// Draw a random seed for each of the two hash functions.
+fn random_seeds() -> [u64; 2] {
+    let state = RandomState::new();
+    [state.hash_one(0u64), state.hash_one(1u64)]
+}
+
+impl<K, V> CuckooTable<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `CuckooTable` with the given number of buckets in each
+    /// of its two arrays.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> CuckooTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `CuckooTable` that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+    /// Create a new `CuckooTable` with the given number of buckets in each
+    /// of its two arrays, hashing keys with `hash_builder`.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            slots: [Self::empty_slots(capacity), Self::empty_slots(capacity)],
+            stash: Vec::new(),
+            seeds: random_seeds(),
+            len: 0,
+            rehashes: 0,
+            hash_builder,
+        }
+    }
+    /// Get a reference to the table's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+    /// Allocate `buckets` empty slots.
+    fn empty_slots(buckets: usize) -> Slots<K, V> {
+        std::iter::repeat_with(|| None).take(buckets).collect()
+    }
+    /// Hash the key with the hash function of one side and return its index.
+    fn index<Q>(&self, side: usize, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        self.bucket(self.seeds[side], self.slots[side].len(), key)
+    }
+    /// Hash the key salted with `seed` and return its index among `buckets`.
+    fn bucket<Q>(&self, seed: u64, buckets: usize, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        let mut hasher = self.hash_builder.build_hasher();
+        hasher.write_u64(seed);
+        key.hash(&mut hasher);
+        (hasher.finish() as usize) % buckets
+    }
+    /// Find the pair for the key in its two slots, then in the stash.
+    fn find<Q>(&self, key: &Q) -> Option<&(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        (0..2)
+            .filter_map(|side| self.slots[side][self.index(side, key)].as_ref())
+            .chain(self.stash.iter())
+            .find(|(k, _)| k.borrow() == key)
+    }
+    /// Find the pair for the key in its two slots, then in the stash, mutably.
+    fn find_mut<Q>(&mut self, key: &Q) -> Option<&mut (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let indexes = [self.index(0, key), self.index(1, key)];
+        let [first, second] = &mut self.slots;
+        first[indexes[0]]
+            .as_mut()
+            .into_iter()
+            .chain(second[indexes[1]].as_mut())
+            .chain(self.stash.iter_mut())
+            .find(|(k, _)| k.borrow() == key)
+    }
+    /// Place a pair whose key is absent, evicting residents back and forth
+    /// between the two arrays. If that doesn't free a slot, the evictions are
+    /// undone and the pair is returned.
+    fn place(&mut self, mut pair: (K, V)) -> Option<(K, V)> {
+        for side in 0..2 {
+            let index = self.index(side, &pair.0);
+            if self.slots[side][index].is_none() {
+                self.slots[side][index] = Some(pair);
+                return None;
+            }
+        }
+        let mut side = 0;
+        let mut kicked = Vec::with_capacity(MAX_KICKS);
+        for _ in 0..MAX_KICKS {
+            let index = self.index(side, &pair.0);
+            match self.slots[side][index].replace(pair) {
+                None => return None,
+                Some(evicted) => pair = evicted,
+            }
+            kicked.push((side, index));
+            side ^= 1;
+        }
+        // Put every evicted pair back, which hands back the one given.
+        for (side, index) in kicked.into_iter().rev() {
+            pair = self.slots[side][index]
+                .replace(pair)
+                .expect("kicked slot is full");
+        }
+        Some(pair)
+    }
+    /// Place a pair whose key is absent, falling back to the stash.
+    /// Returns the pair if the stash is full too.
+    fn place_or_stash(&mut self, pair: (K, V)) -> Option<(K, V)> {
+        let pair = self.place(pair)?;
+        if self.stash.len() < STASH_SIZE {
+            self.stash.push(pair);
+            return None;
+        }
+        Some(pair)
+    }
+    /// Move every pair out of the slots and the stash.
+    fn take_all(&mut self) -> Vec<(K, V)> {
+        let [first, second] = &mut self.slots;
+        first
+            .iter_mut()
+            .chain(second.iter_mut())
+            .filter_map(Option::take)
+            .chain(self.stash.drain(..))
+            .collect()
+    }
+    /// Plan where every key goes with `seeds` and `buckets` per array, kicking
+    /// keys between the arrays and stashing them as inserts do, without moving
+    /// any pair. Returns `None` if a key is left over once the stash is full.
+    fn plan(&self, keys: &[&K], seeds: [u64; 2], buckets: usize) -> Option<Vec<Location>> {
+        let mut slots = [vec![None; buckets], vec![None; buckets]];
+        let mut stashed = 0;
+        'keys: for mut id in 0..keys.len() {
+            let bucket = |side: usize, id: usize| self.bucket(seeds[side], buckets, keys[id]);
+            for (side, slots) in slots.iter_mut().enumerate() {
+                let slot = &mut slots[bucket(side, id)];
+                if slot.is_none() {
+                    *slot = Some(id);
+                    continue 'keys;
+                }
+            }
+            let mut side = 0;
+            for _ in 0..MAX_KICKS {
+                match slots[side][bucket(side, id)].replace(id) {
+                    None => continue 'keys,
+                    Some(evicted) => id = evicted,
+                }
+                side ^= 1;
+            }
+            if stashed == STASH_SIZE {
+                return None;
+            }
+            stashed += 1;
+        }
+        let mut locations = vec![Location::Stash; keys.len()];
+        for (side, slots) in slots.iter().enumerate() {
+            for (index, id) in slots.iter().enumerate() {
+                if let Some(id) = id {
+                    locations[*id] = Location::Slot(side, index);
+                }
+            }
+        }
+        Some(locations)
+    }
+    /// Rebuild the table with `buckets` per array and fresh seeds, placing
+    /// `extra` along with the stored pairs. Each failed attempt reseeds, and
+    /// the table doubles after a few attempts at the same size. If every
+    /// attempt fails, `extra` is dropped and the table is left as it was.
+    fn rehash(&mut self, mut buckets: usize, extra: Option<(K, V)>) -> Result<()> {
+        let mut attempts = 0;
+        let planned = {
+            let mut keys: Vec<&K> = self.iter().map(|(k, _)| k).collect();
+            keys.extend(extra.as_ref().map(|(k, _)| k));
+            loop {
+                attempts += 1;
+                let seeds = random_seeds();
+                if let Some(locations) = self.plan(&keys, seeds, buckets) {
+                    break Some((seeds, locations));
+                }
+                if attempts == MAX_RESEEDS * MAX_GROWS {
+                    break None;
+                }
+                if attempts % MAX_RESEEDS == 0 {
+                    buckets *= 2;
+                }
+            }
+        };
+        self.rehashes += attempts;
+        let (seeds, locations) = planned.ok_or(Error::TooManyCollisions)?;
+        // `take_all` yields the pairs in the same order as `iter`.
+        let mut pairs = self.take_all();
+        pairs.extend(extra);
+        self.seeds = seeds;
+        self.slots = [Self::empty_slots(buckets), Self::empty_slots(buckets)];
+        for (pair, location) in pairs.into_iter().zip(locations) {
+            match location {
+                Location::Slot(side, index) => self.slots[side][index] = Some(pair),
+                Location::Stash => self.stash.push(pair),
+            }
+        }
+        Ok(())
+    }
+    /// Insert a new key-value pair into the table.
+    /// If the key is already present, its value is replaced and returned.
+    /// Fails, leaving the table unchanged, if no seeds tried place every key.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>> {
+        if let Some((_, stored)) = self.find_mut(&key) {
+            return Ok(Some(mem::replace(stored, value)));
+        }
+        // Two arrays with one pair per bucket cuckoo reliably up to half full.
+        let buckets = self.slots[0].len();
+        if self.len + 1 > buckets {
+            self.rehash((buckets * 2).max(MIN_BUCKETS), None)?;
+        }
+        if let Some(pair) = self.place_or_stash((key, value)) {
+            let buckets = self.slots[0].len();
+            self.rehash(buckets, Some(pair))?;
+        }
+        self.len += 1;
+        Ok(None)
+    }
+    /// Get the value for the given key.
+    pub fn get<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).map(|(_, v)| v).ok_or(Error::KeyNotFound)
+    }
+    /// Get the stored key and value for the given key.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Result<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key)
+            .map(|(k, v)| (k, v))
+            .ok_or(Error::KeyNotFound)
+    }
+    /// Check if the table contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+    /// Update the value for the given key.
+    pub fn update<Q>(&mut self, key: &Q) -> Result<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        self.find_mut(key).map(|(_, v)| v).ok_or(Error::KeyNotFound)
+    }
+    /// Remove the key-value pair from the table.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+    /// Remove the key-value pair from the table, returning the stored key too.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Result<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        let mut removed = None;
+        for side in 0..2 {
+            let index = self.index(side, key);
+            let slot = &mut self.slots[side][index];
+            if slot.as_ref().is_some_and(|(k, _)| k.borrow() == key) {
+                removed = slot.take();
+                break;
+            }
+        }
+        let removed = match removed {
+            Some(pair) => {
+                self.unstash();
+                pair
+            }
+            None => {
+                let position = self
+                    .stash
+                    .iter()
+                    .position(|(k, _)| k.borrow() == key)
+                    .ok_or(Error::KeyNotFound)?;
+                self.stash.swap_remove(position)
+            }
+        };
+        self.len -= 1;
+        Ok(removed)
+    }
+    /// Move stashed pairs whose slots have freed up back into the arrays.
+    fn unstash(&mut self) {
+        let stash = mem::take(&mut self.stash);
+        for pair in stash {
+            let free = (0..2).find(|&side| self.slots[side][self.index(side, &pair.0)].is_none());
+            match free {
+                Some(side) => {
+                    let index = self.index(side, &pair.0);
+                    self.slots[side][index] = Some(pair);
+                }
+                None => self.stash.push(pair),
+            }
+        }
+    }
+    /// Iterate over the key-value pairs of the table.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let [first, second] = &self.slots;
+        first
+            .iter()
+            .chain(second.iter())
+            .flatten()
+            .chain(self.stash.iter())
+            .map(|(k, v)| (k, v))
+    }
+    /// Get the number of elements in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get the number of slots in the table, across both arrays.
+    pub fn capacity(&self) -> usize {
+        self.slots[0].len() + self.slots[1].len()
+    }
+    /// Get the number of pairs held in the stash.
+    pub fn stash_len(&self) -> usize {
+        self.stash.len()
+    }
+    /// Get the number of times the table drew fresh seeds and rehashed.
+    pub fn rehash_count(&self) -> usize {
+        self.rehashes
+    }
+}
+/// Default implementation for `CuckooTable`.
+impl<K, V, S> Default for CuckooTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_capacity_and_hasher(64, S::default())
+    }
+}
+
+impl<K, V, S> Debug for CuckooTable<K, V, S>
+where
+    K: Hash + Eq + Debug,
+    V: Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::BuildHasherDefault;
+
+    /// Hasher keeping only the last `u64` written, so a key's slot
+    /// depends on the seed alone.
+    #[derive(Default)]
+    struct SeedOnlyHasher(u64);
+
+    impl Hasher for SeedOnlyHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, _bytes: &[u8]) {}
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n;
+        }
+    }
+
+    #[test]
+    fn test_cuckoo_table_ops() {
+        let mut table = CuckooTable::new(4);
+        assert_eq!(table.insert("key1", "value1").unwrap(), None);
+        assert_eq!(table.get("key1").unwrap(), &"value1");
+        assert_eq!(table.insert("key1", "value2").unwrap(), Some("value1"));
+        *table.update("key1").unwrap() = "value3";
+        assert_eq!(table.get_key_value("key1").unwrap(), (&"key1", &"value3"));
+        assert!(table.contains_key("key1"));
+        assert_eq!(table.remove("key1").unwrap(), "value3");
+        assert!(table.get("key1").is_err());
+        assert!(table.is_empty());
+        assert!(matches!(table.remove("key1"), Err(Error::EmptyTable)));
+    }
+
+    #[test]
+    fn test_cuckoo_table_load() {
+        let mut table = CuckooTable::new(0);
+        for i in 0..5_000 {
+            table.insert(i, i * 2).unwrap();
+        }
+        assert_eq!(table.len(), 5_000);
+        assert!(table.stash_len() <= STASH_SIZE);
+        for i in 0..5_000 {
+            assert_eq!(table.get(&i).unwrap(), &(i * 2));
+        }
+        for i in (0..5_000).step_by(2) {
+            assert_eq!(table.remove(&i).unwrap(), i * 2);
+        }
+        assert_eq!(table.iter().count(), 2_500);
+        assert!(table.get(&10).is_err());
+        assert_eq!(table.get(&11).unwrap(), &22);
+    }
+
+    #[test]
+    fn test_cuckoo_table_cycles() {
+        // Every key hashes to the same two slots, so the third insert cycles
+        // and lands in the stash, and the seventh forces a rehash.
+        let mut table: CuckooTable<i32, i32, BuildHasherDefault<SeedOnlyHasher>> =
+            CuckooTable::with_capacity_and_hasher(64, BuildHasherDefault::default());
+        for i in 0..6 {
+            table.insert(i, i).unwrap();
+            assert_eq!(table.rehash_count(), 0);
+            assert_eq!(table.stash_len(), (i as usize).saturating_sub(1));
+        }
+        // No seed splits keys that hash alike, so the insert fails
+        // instead of growing the stash, and the table is left as it was.
+        assert!(matches!(table.insert(6, 6), Err(Error::TooManyCollisions)));
+        assert_eq!(table.rehash_count(), MAX_RESEEDS * MAX_GROWS);
+        assert_eq!(table.capacity(), 128);
+        assert_eq!(table.stash_len(), STASH_SIZE);
+        assert_eq!(table.len(), 6);
+        assert!(table.get(&6).is_err());
+        for i in 0..6 {
+            assert_eq!(table.get(&i).unwrap(), &i);
+        }
+        assert_eq!(table.remove(&3).unwrap(), 3);
+        assert_eq!(table.insert(6, 6).unwrap(), None);
+        assert_eq!(table.len(), 6);
+        assert!(table.get(&3).is_err());
+        assert_eq!(table.get(&6).unwrap(), &6);
+    }
+
+    #[test]
+    fn test_cuckoo_table_random_seeds() {
+        let first: CuckooTable<i32, i32> = CuckooTable::new(8);
+        let second: CuckooTable<i32, i32> = CuckooTable::new(8);
+        assert_ne!(first.seeds, second.seeds);
+    }
+}
+// endregion: --- Tests
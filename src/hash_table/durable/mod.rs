@@ -0,0 +1,499 @@
+use super::snapshot::{crc32, io_error};
+use super::{Codec, Error, Result, Table};
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the snapshot file inside a table's directory.
+const SNAPSHOT_FILE: &str = "table.snapshot";
+/// Name of the snapshot file while `compact` is still writing it.
+const SNAPSHOT_TMP_FILE: &str = "table.snapshot.tmp";
+/// Name of the write-ahead log inside a table's directory.
+const LOG_FILE: &str = "table.wal";
+/// Size of the payload length and the two checksums ahead of every record.
+const RECORD_HEADER_LEN: usize = 8 + 4 + 4;
+/// Tag of a record storing a key and its new value.
+const PUT: u8 = 0;
+/// Tag of a record removing a key.
+const REMOVE: u8 = 1;
+
+/// A record read from the front of the log.
+enum Record<'a> {
+    /// A whole record with a matching checksum, holding this payload.
+    Complete(&'a [u8]),
+    /// A record cut short by the end of the log, as left by a crash mid-write.
+    Torn,
+    /// A record whose header or payload doesn't match its checksum.
+    Corrupt,
+}
+
+/// `DurableTable` is a `Table` that survives restarts by logging every change.
+///
+/// Each `insert`, `update` and `remove` is appended to a write-ahead log
+/// before it is applied in memory. Opening the table loads the last snapshot
+/// and replays the log on top of it, dropping a final record torn by a crash.
+/// `compact` writes the whole table to a fresh snapshot and empties the log.
+///
+/// Records are `Codec` encoded and framed by their length and two CRC-32s:
+///
+/// | Field           | Size     | Contents                                    |
+/// |-----------------|----------|---------------------------------------------|
+/// | length          | 8 bytes  | payload size, little-endian                 |
+/// | payload CRC     | 4 bytes  | CRC-32 of the payload                       |
+/// | header CRC      | 4 bytes  | CRC-32 of the length and payload CRC        |
+/// | payload         | variable | a tag, the key, and for a put the new value |
+///
+/// Records aren't flushed to disk as they are written, so a crash can leave
+/// the end of the log cut short, filled with zeros or with garbage. A bad
+/// record counts as torn when no whole record follows it, and is dropped.
+/// A bad record with whole records after it fails with
+/// `Error::CorruptSnapshot` and leaves the log as it is, so no committed
+/// record is ever dropped.
+///
+/// Records are handed to the OS as they are written, so they outlive the
+/// process. Call `sync` to also flush them to disk.
+pub struct DurableTable<K, V, S = RandomState> {
+    table: Table<K, V, S>,
+    dir: PathBuf,
+    log: File,
+    /// Size of the complete records in the log.
+    log_len: u64,
+}
+
+impl<K, V> DurableTable<K, V, RandomState>
+where
+    K: Hash + Eq + Codec,
+    V: Codec,
+{
+    /// Open the table stored in `dir`, creating it if it doesn't exist.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_hasher(dir, RandomState::new())
+    }
+}
+
+impl<K, V, S> DurableTable<K, V, S>
+where
+    K: Hash + Eq + Codec,
+    V: Codec,
+    S: BuildHasher,
+{
+    /// Open the table stored in `dir`, creating it if it doesn't exist,
+    /// and hash keys with `hash_builder`.
+    pub fn open_with_hasher(dir: impl AsRef<Path>, hash_builder: S) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error)?;
+        let table = match File::open(dir.join(SNAPSHOT_FILE)) {
+            Ok(file) => Table::read_snapshot_with_hasher(file, hash_builder)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Table::with_hasher(hash_builder),
+            Err(err) => return Err(Error::Io(err)),
+        };
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(dir.join(LOG_FILE))
+            .map_err(io_error)?;
+        let mut records = Vec::new();
+        log.read_to_end(&mut records).map_err(io_error)?;
+
+        let mut durable = Self {
+            table,
+            dir,
+            log,
+            log_len: 0,
+        };
+        durable.log_len = durable.replay(&records)? as u64;
+        if durable.log_len < records.len() as u64 {
+            // Drop the torn record, so new records follow the last whole one.
+            durable.log.set_len(durable.log_len).map_err(io_error)?;
+        }
+        Ok(durable)
+    }
+    /// Apply the records of a log, returning the size of the whole ones.
+    fn replay(&mut self, mut log: &[u8]) -> Result<usize> {
+        let mut replayed = 0;
+        while !log.is_empty() {
+            match Self::read_record(log) {
+                Record::Complete(payload) => {
+                    self.apply(payload)?;
+                    let len = RECORD_HEADER_LEN + payload.len();
+                    replayed += len;
+                    log = &log[len..];
+                }
+                Record::Torn => break,
+                Record::Corrupt if Self::is_tail(log) => break,
+                Record::Corrupt => return Err(Error::CorruptSnapshot),
+            }
+        }
+        Ok(replayed)
+    }
+    /// Read the record at the front of `log`.
+    fn read_record(log: &[u8]) -> Record<'_> {
+        let Some((header, rest)) = log.split_at_checked(RECORD_HEADER_LEN) else {
+            return Record::Torn;
+        };
+        let (framing, header_checksum) = header.split_at(8 + 4);
+        if crc32(framing).to_le_bytes() != header_checksum {
+            return Record::Corrupt;
+        }
+        let (len, checksum) = framing.split_at(8);
+        let len = u64::from_le_bytes(len.try_into().expect("sized slice"));
+        let checksum = u32::from_le_bytes(checksum.try_into().expect("sized slice"));
+        match usize::try_from(len).ok().and_then(|len| rest.get(..len)) {
+            None => Record::Torn,
+            Some(payload) if crc32(payload) == checksum => Record::Complete(payload),
+            Some(_) => Record::Corrupt,
+        }
+    }
+    /// Check if no whole record starts after the front of `log`, so a bad
+    /// record there was left by a crash rather than damage to the log.
+    fn is_tail(log: &[u8]) -> bool {
+        !(1..log.len()).any(|start| matches!(Self::read_record(&log[start..]), Record::Complete(_)))
+    }
+    /// Apply the change held by a record payload to the table.
+    fn apply(&mut self, mut payload: &[u8]) -> Result<()> {
+        match u8::decode(&mut payload)? {
+            PUT => {
+                let key = K::decode(&mut payload)?;
+                let value = V::decode(&mut payload)?;
+                self.table.insert(key, value);
+            }
+            REMOVE => {
+                let key = K::decode(&mut payload)?;
+                let _ = self.table.remove(&key);
+            }
+            _ => return Err(Error::CorruptSnapshot),
+        }
+        match payload.is_empty() {
+            true => Ok(()),
+            false => Err(Error::CorruptSnapshot),
+        }
+    }
+    /// Append a record holding `payload` to the log.
+    fn append(&mut self, payload: &[u8]) -> Result<()> {
+        let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+        record.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        record.extend_from_slice(&crc32(payload).to_le_bytes());
+        let header_checksum = crc32(&record);
+        record.extend_from_slice(&header_checksum.to_le_bytes());
+        record.extend_from_slice(payload);
+        if let Err(err) = self.log.write_all(&record) {
+            // Cut off any part of the record that was written, so later
+            // records don't follow a corrupt one.
+            let _ = self.log.set_len(self.log_len);
+            return Err(io_error(err));
+        }
+        self.log_len += record.len() as u64;
+        Ok(())
+    }
+    /// Encode a record storing a key and its new value.
+    fn put_record(key: &K, value: &V) -> Vec<u8> {
+        let mut payload = vec![PUT];
+        key.encode(&mut payload);
+        value.encode(&mut payload);
+        payload
+    }
+    /// Log and insert a key-value pair.
+    /// Returns the value previously stored for the key, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>> {
+        self.append(&Self::put_record(&key, &value))?;
+        Ok(self.table.insert(key, value))
+    }
+    /// Get the value for the given key.
+    pub fn get<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(key)
+    }
+    /// Check if the table contains the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(key)
+    }
+    /// Call `f` with the value for the given key, then log and store the
+    /// modified value. `f` works on a copy, so nothing changes if logging fails.
+    pub fn update<Q, F, R>(&mut self, key: &Q, f: F) -> Result<R>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+        F: FnOnce(&mut V) -> R,
+    {
+        let (stored, value) = self.table.get_key_value(key)?;
+        let mut value = value.clone();
+        let result = f(&mut value);
+        let record = Self::put_record(stored, &value);
+        self.append(&record)?;
+        *self.table.update(key)? = value;
+        Ok(result)
+    }
+    /// Log the removal of the given key and remove it from the table.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (stored, _) = self.table.get_key_value(key)?;
+        let mut record = vec![REMOVE];
+        stored.encode(&mut record);
+        self.append(&record)?;
+        self.table.remove(key)
+    }
+    /// Write the whole table to a fresh snapshot and empty the log.
+    pub fn compact(&mut self) -> Result<()> {
+        let tmp = self.dir.join(SNAPSHOT_TMP_FILE);
+        let mut file = File::create(&tmp).map_err(io_error)?;
+        self.table.write_snapshot(&mut file)?;
+        file.sync_all().map_err(io_error)?;
+        fs::rename(&tmp, self.dir.join(SNAPSHOT_FILE)).map_err(io_error)?;
+        // The rename must reach the disk before the log is emptied, or a
+        // crash could lose both the new snapshot and the log.
+        sync_dir(&self.dir)?;
+        // Crashing before the log is emptied replays changes the snapshot
+        // already holds, which leaves every key with the same value.
+        self.log.set_len(0).map_err(io_error)?;
+        self.log.sync_all().map_err(io_error)?;
+        self.log_len = 0;
+        Ok(())
+    }
+    /// Flush the log to disk.
+    pub fn sync(&self) -> Result<()> {
+        self.log.sync_data().map_err(io_error)
+    }
+    /// Get the in-memory table, for read-only operations.
+    pub fn table(&self) -> &Table<K, V, S> {
+        &self.table
+    }
+    /// Iterate over the pairs of the table.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table.iter()
+    }
+    /// Get the size in bytes of the log, to decide when to `compact`.
+    pub fn log_len(&self) -> u64 {
+        self.log_len
+    }
+    /// Get the directory the table is stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Get the number of elements in the table.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+/// Flush the entries of a directory to disk, so a rename in it survives a crash.
+fn sync_dir(dir: &Path) -> Result<()> {
+    // Directories can't be opened as files on every platform.
+    if cfg!(unix) {
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(io_error)?;
+    }
+    Ok(())
+}
+
+impl<K, V, S> Debug for DurableTable<K, V, S>
+where
+    K: Hash + Eq + Codec + Debug,
+    V: Codec + Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory removed when the test ends.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rust-ds-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+        fn log(&self) -> PathBuf {
+            self.0.join(LOG_FILE)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn open(dir: &TempDir) -> Result<DurableTable<String, u64>> {
+        DurableTable::open(&dir.0)
+    }
+
+    #[test]
+    fn test_durable_table_replay() {
+        let dir = TempDir::new("replay");
+        let mut table = open(&dir).unwrap();
+        assert!(table.is_empty());
+        for i in 0..50 {
+            assert_eq!(table.insert(format!("key{}", i), i).unwrap(), None);
+        }
+        assert_eq!(table.insert("key0".to_string(), 100).unwrap(), Some(0));
+        assert_eq!(
+            table.update("key1", |v| std::mem::replace(v, 7)).unwrap(),
+            1
+        );
+        assert_eq!(table.remove("key2").unwrap(), 2);
+        let log_len = table.log_len();
+        assert!(matches!(table.remove("key2"), Err(Error::KeyNotFound)));
+        assert!(table.update("missing", |v| *v += 1).is_err());
+        assert_eq!(table.log_len(), log_len);
+        table.sync().unwrap();
+        drop(table);
+
+        let table = open(&dir).unwrap();
+        assert_eq!(table.len(), 49);
+        assert_eq!(table.get("key0").unwrap(), &100);
+        assert_eq!(table.get("key1").unwrap(), &7);
+        assert!(!table.contains_key("key2"));
+        assert_eq!(table.get("key49").unwrap(), &49);
+        assert_eq!(table.log_len(), log_len);
+    }
+
+    #[test]
+    fn test_durable_table_torn_record() {
+        let dir = TempDir::new("torn");
+        let mut table = open(&dir).unwrap();
+        table.insert("a".to_string(), 1).unwrap();
+        table.insert("b".to_string(), 2).unwrap();
+        let log_len = table.log_len();
+        drop(table);
+
+        // A crash mid-write leaves part of a record at the end of the log.
+        let whole = fs::read(dir.log()).unwrap();
+        let last = whole.len() - log_len as usize / 2;
+        for cut in [3, RECORD_HEADER_LEN + 2, log_len as usize / 2 - 1] {
+            let mut log = whole.clone();
+            log.extend_from_slice(&whole[last..last + cut]);
+            fs::write(dir.log(), &log).unwrap();
+            let table = open(&dir).unwrap();
+            assert_eq!(table.len(), 2);
+            assert_eq!(table.log_len(), log_len);
+            assert_eq!(fs::metadata(dir.log()).unwrap().len(), log_len);
+        }
+
+        // New records follow the last whole one.
+        let mut table = open(&dir).unwrap();
+        table.insert("c".to_string(), 3).unwrap();
+        drop(table);
+        let table = open(&dir).unwrap();
+        assert_eq!(table.get("c").unwrap(), &3);
+        drop(table);
+
+        // A bad record at the end was never flushed whole, so it is dropped.
+        let whole = fs::read(dir.log()).unwrap();
+        let mut log = whole.clone();
+        *log.last_mut().unwrap() ^= 1;
+        fs::write(dir.log(), &log).unwrap();
+        let table = open(&dir).unwrap();
+        assert_eq!(table.len(), 2);
+        assert!(!table.contains_key("c"));
+        assert_eq!(fs::metadata(dir.log()).unwrap().len(), log_len);
+        drop(table);
+
+        // A bad record in the middle of the log is corrupt.
+        let mut log = whole;
+        log[RECORD_HEADER_LEN] ^= 1;
+        fs::write(dir.log(), &log).unwrap();
+        assert!(matches!(open(&dir), Err(Error::CorruptSnapshot)));
+        assert_eq!(fs::read(dir.log()).unwrap(), log);
+    }
+
+    #[test]
+    fn test_durable_table_zeroed_tail() {
+        let dir = TempDir::new("zeroed-tail");
+        let mut table = open(&dir).unwrap();
+        table.insert("a".to_string(), 1).unwrap();
+        table.insert("b".to_string(), 2).unwrap();
+        let log_len = table.log_len();
+        drop(table);
+
+        // A crash can leave the space of unflushed records filled with zeros.
+        let mut log = fs::read(dir.log()).unwrap();
+        log.extend_from_slice(&[0; 64]);
+        fs::write(dir.log(), &log).unwrap();
+        let table = open(&dir).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.log_len(), log_len);
+        assert_eq!(fs::metadata(dir.log()).unwrap().len(), log_len);
+    }
+
+    #[test]
+    fn test_durable_table_corrupt_length() {
+        let dir = TempDir::new("corrupt-length");
+        let mut table = open(&dir).unwrap();
+        for i in 0..10 {
+            table.insert(format!("key{}", i), i).unwrap();
+        }
+        drop(table);
+
+        // A flipped bit in a length must not pass for a torn record, which
+        // would truncate every record after it.
+        let whole = fs::read(dir.log()).unwrap();
+        let record_len = whole.len() / 10;
+        for bit in [0, 7, 8 * 7 + 7] {
+            let mut log = whole.clone();
+            log[record_len * 4 + bit / 8] ^= 1 << (bit % 8);
+            fs::write(dir.log(), &log).unwrap();
+            assert!(matches!(open(&dir), Err(Error::CorruptSnapshot)));
+            assert_eq!(fs::read(dir.log()).unwrap(), log);
+        }
+    }
+
+    #[test]
+    fn test_durable_table_compact() {
+        let dir = TempDir::new("compact");
+        let mut table = open(&dir).unwrap();
+        for i in 0..20 {
+            table.insert(i.to_string(), i).unwrap();
+        }
+        for i in 0..10 {
+            table.remove(&i.to_string()).unwrap();
+        }
+        let log = fs::read(dir.log()).unwrap();
+        table.compact().unwrap();
+        assert_eq!(table.log_len(), 0);
+        table.insert("new".to_string(), 1).unwrap();
+        drop(table);
+
+        let table = open(&dir).unwrap();
+        assert_eq!(table.len(), 11);
+        assert_eq!(table.get("15").unwrap(), &15);
+        assert_eq!(table.get("new").unwrap(), &1);
+        drop(table);
+
+        // A crash between writing the snapshot and emptying the log replays
+        // changes the snapshot already holds.
+        fs::write(dir.log(), log).unwrap();
+        let table = open(&dir).unwrap();
+        assert_eq!(table.len(), 10);
+        assert!(!table.contains_key("5"));
+        assert_eq!(table.get("19").unwrap(), &19);
+    }
+}
+// endregion: --- Tests
@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::io;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -6,7 +7,13 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub enum Error {
     EmptyTable,
     KeyNotFound,
+    KeyExists,
     InvalidCapacity,
+    InvalidLoadFactor,
+    IndexOutOfBounds,
+    CorruptSnapshot,
+    TooManyCollisions,
+    Io(io::Error),
 }
 
 impl Display for Error {
@@ -16,9 +23,31 @@ impl Display for Error {
             Error::KeyNotFound => {
                 write!(f, "Operation failed: Key not found in table")
             }
+            Error::KeyExists => {
+                write!(f, "Operation failed: Key already exists in table")
+            }
             Error::InvalidCapacity => {
                 write!(f, "Operation failed: Invalid capacity")
             }
+            Error::InvalidLoadFactor => {
+                write!(f, "Operation failed: Invalid load factor")
+            }
+            Error::IndexOutOfBounds => {
+                write!(f, "Index is out of bounds")
+            }
+            Error::CorruptSnapshot => {
+                write!(
+                    f,
+                    "Operation failed: Snapshot or log is truncated or corrupt"
+                )
+            }
+            Error::TooManyCollisions => {
+                write!(
+                    f,
+                    "Operation failed: Too many keys collide to place them all"
+                )
+            }
+            Error::Io(err) => write!(f, "Operation failed: I/O error: {}", err),
         }
     }
 }
@@ -0,0 +1,93 @@
+//! Time sources for `ExpiringTable`.
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// `Clock` tells an `ExpiringTable` what time it is.
+pub trait Clock {
+    /// Get the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// `SystemClock` reads the monotonic system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// `ManualClock` only moves when it is advanced, so tests can expire entries
+/// deterministically. Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// Create a new `ManualClock` stopped at the current instant.
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+    /// Move the clock forward by `duration`, stopping at the latest instant
+    /// the platform can represent instead of overflowing.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(later) = now.checked_add(duration) {
+            *now = later;
+            return;
+        }
+        // Close in on the last instant by adding ever smaller steps.
+        let mut step = duration;
+        while !step.is_zero() {
+            match now.checked_add(step) {
+                Some(later) => *now = later,
+                None => step /= 2,
+            }
+        }
+    }
+}
+/// Default implementation for `ManualClock`.
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new();
+        let shared = clock.clone();
+        let start = clock.now();
+        shared.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+        assert!(SystemClock.now() >= start);
+    }
+
+    #[test]
+    fn test_manual_clock_saturates() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        clock.advance(Duration::MAX);
+        let end = clock.now();
+        assert!(end > start);
+        assert!(end.checked_add(Duration::from_nanos(1)).is_none());
+        clock.advance(Duration::MAX);
+        assert_eq!(clock.now(), end);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,257 @@
+mod clock;
+
+use super::{Error, Result, Table};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
+
+pub use clock::{Clock, ManualClock, SystemClock};
+
+/// A stored value along with the instant it expires at, if any.
+type Expiring<V> = (V, Option<Instant>);
+
+/// `ExpiringTable` is a `Table` whose entries expire after a time-to-live.
+///
+/// Every entry gets the table's default TTL, or its own through
+/// `insert_with_ttl`. Expired entries are dropped lazily when they are looked
+/// up, and in bulk by `purge_expired`. Time is read from the `Clock` given as
+/// `C`, so tests can drive it with a `ManualClock`.
+pub struct ExpiringTable<K, V, C = SystemClock, S = RandomState> {
+    table: Table<K, Expiring<V>, S>,
+    default_ttl: Duration,
+    clock: C,
+}
+
+impl<K, V> ExpiringTable<K, V, SystemClock, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `ExpiringTable` whose entries live for `default_ttl`.
+    pub fn new(default_ttl: Duration) -> Self {
+        Self::with_clock_and_hasher(default_ttl, SystemClock, RandomState::new())
+    }
+}
+
+impl<K, V, C> ExpiringTable<K, V, C, RandomState>
+where
+    K: Hash + Eq,
+    C: Clock,
+{
+    /// Create a new `ExpiringTable` whose entries live for `default_ttl`,
+    /// reading time from `clock`.
+    pub fn with_clock(default_ttl: Duration, clock: C) -> Self {
+        Self::with_clock_and_hasher(default_ttl, clock, RandomState::new())
+    }
+}
+
+impl<K, V, C, S> ExpiringTable<K, V, C, S>
+where
+    K: Hash + Eq,
+    C: Clock,
+    S: BuildHasher,
+{
+    /// Create a new `ExpiringTable` whose entries live for `default_ttl`,
+    /// reading time from `clock` and hashing keys with `hash_builder`.
+    pub fn with_clock_and_hasher(default_ttl: Duration, clock: C, hash_builder: S) -> Self {
+        Self {
+            table: Table::with_hasher(hash_builder),
+            default_ttl,
+            clock,
+        }
+    }
+    /// Get a reference to the table's `Clock`.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+    /// Get the time-to-live given to entries inserted with `insert`.
+    pub fn default_ttl(&self) -> Duration {
+        self.default_ttl
+    }
+    /// Set the time-to-live given to entries inserted from now on with `insert`.
+    pub fn set_default_ttl(&mut self, default_ttl: Duration) {
+        self.default_ttl = default_ttl;
+    }
+    /// Check if a deadline has passed.
+    fn is_expired(deadline: Option<Instant>, now: Instant) -> bool {
+        deadline.is_some_and(|deadline| deadline <= now)
+    }
+    /// Drop the entry for the key if it has expired.
+    fn expire<Q>(&mut self, key: &Q) -> Result<()>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (_, deadline) = self.table.get(key)?;
+        if Self::is_expired(*deadline, self.clock.now()) {
+            self.table.remove(key)?;
+            return Err(Error::KeyNotFound);
+        }
+        Ok(())
+    }
+    /// Insert a key-value pair that expires after the default time-to-live.
+    /// If the key held a live value, it is replaced and returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with_ttl(key, value, self.default_ttl)
+    }
+    /// Insert a key-value pair that expires after `ttl`.
+    /// If the key held a live value, it is replaced and returned.
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        // A deadline too far to represent never comes.
+        let deadline = now.checked_add(ttl);
+        self.table
+            .insert(key, (value, deadline))
+            .filter(|(_, deadline)| !Self::is_expired(*deadline, now))
+            .map(|(value, _)| value)
+    }
+    /// Get the value for the given key, dropping it if it has expired.
+    pub fn get<Q>(&mut self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.expire(key)?;
+        self.table.get(key).map(|(value, _)| value)
+    }
+    /// Update the value for the given key, dropping it if it has expired.
+    /// The entry keeps its deadline.
+    pub fn update<Q>(&mut self, key: &Q) -> Result<&mut V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.expire(key)?;
+        self.table.update(key).map(|(value, _)| value)
+    }
+    /// Check if the table holds a live value for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table
+            .get(key)
+            .is_ok_and(|(_, deadline)| !Self::is_expired(*deadline, self.clock.now()))
+    }
+    /// Get the time left before the entry for the key expires.
+    /// Entries whose deadline is too far to represent report `Duration::MAX`.
+    pub fn ttl<Q>(&mut self, key: &Q) -> Result<Duration>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.expire(key)?;
+        let (_, deadline) = self.table.get(key)?;
+        Ok(deadline.map_or(Duration::MAX, |deadline| {
+            deadline.duration_since(self.clock.now())
+        }))
+    }
+    /// Remove the key-value pair from the table.
+    /// An expired entry is removed too, but reported as missing.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, deadline) = self.table.remove(key)?;
+        if Self::is_expired(deadline, self.clock.now()) {
+            return Err(Error::KeyNotFound);
+        }
+        Ok(value)
+    }
+    /// Remove every expired entry, returning the evicted pairs.
+    pub fn purge_expired(&mut self) -> Vec<(K, V)> {
+        let now = self.clock.now();
+        self.table
+            .extract_if(|_, (_, deadline)| Self::is_expired(*deadline, now))
+            .map(|(key, (value, _))| (key, value))
+            .collect()
+    }
+    /// Iterate over the live key-value pairs of the table.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let now = self.clock.now();
+        self.table
+            .iter()
+            .filter(move |(_, (_, deadline))| !Self::is_expired(*deadline, now))
+            .map(|(key, (value, _))| (key, value))
+    }
+    /// Get the number of entries in the table.
+    /// Expired entries count until they are looked up or purged.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_expiring_table_ops() {
+        let mut table = ExpiringTable::new(Duration::from_secs(60));
+        assert_eq!(table.insert("session", 1), None);
+        assert_eq!(table.get("session").unwrap(), &1);
+        *table.update("session").unwrap() += 1;
+        assert_eq!(table.insert("session", 3), Some(2));
+        assert!(table.contains_key("session"));
+        assert!(table.ttl("session").unwrap() <= Duration::from_secs(60));
+        assert_eq!(table.remove("session").unwrap(), 3);
+        assert!(table.get("session").is_err());
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_expiring_table_lazy_expiry() {
+        let clock = ManualClock::new();
+        let mut table = ExpiringTable::with_clock(10 * SECOND, clock.clone());
+        table.insert("a", 1);
+        table.insert_with_ttl("b", 2, 30 * SECOND);
+        table.insert_with_ttl("forever", 3, Duration::MAX);
+
+        clock.advance(10 * SECOND);
+        assert!(!table.contains_key("a"));
+        assert_eq!(table.len(), 3);
+        assert!(table.get("a").is_err());
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.ttl("b").unwrap(), 20 * SECOND);
+        assert_eq!(table.ttl("forever").unwrap(), Duration::MAX);
+
+        table.insert("a", 4);
+        clock.advance(10 * SECOND);
+        assert_eq!(table.insert("a", 5), None);
+        assert!(table.update("b").is_ok());
+        clock.advance(10 * SECOND);
+        assert!(table.remove("b").is_err());
+        assert_eq!(table.iter().count(), 1);
+        assert_eq!(table.get("forever").unwrap(), &3);
+    }
+
+    #[test]
+    fn test_expiring_table_purge() {
+        let clock = ManualClock::new();
+        let mut table = ExpiringTable::with_clock(5 * SECOND, clock.clone());
+        for i in 0..10 {
+            table.insert_with_ttl(i, i * 10, (i as u32 + 1) * SECOND);
+        }
+        clock.advance(4 * SECOND);
+        let mut evicted = table.purge_expired();
+        evicted.sort();
+        assert_eq!(evicted, vec![(0, 0), (1, 10), (2, 20), (3, 30)]);
+        assert_eq!(table.len(), 6);
+        assert!(table.purge_expired().is_empty());
+
+        table.set_default_ttl(SECOND);
+        table.insert(42, 420);
+        clock.advance(SECOND);
+        assert!(!table.contains_key(&42));
+        assert_eq!(table.purge_expired().len(), 2);
+    }
+}
+// endregion: --- Tests
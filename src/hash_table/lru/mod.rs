@@ -0,0 +1,278 @@
+use super::{Error, Result, Table};
+use crate::linked_lists::{Double, ExtNode};
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::rc::Rc;
+
+/// Node of the recency list, shared between the list and the table entry.
+type Link<K> = Rc<RefCell<ExtNode<K>>>;
+/// Callback receiving the entries evicted to make room.
+type OnEvict<K, V> = Box<dyn FnMut(K, V)>;
+
+/// `LruCache` is a fixed-capacity cache evicting the least recently used entry.
+///
+/// Entries live in a `Table`, and their recency is tracked by a `Double`
+/// linked list of keys, from least to most recently used. Each table entry
+/// keeps a handle to its list node, so every operation runs in O(1).
+pub struct LruCache<K, V, S = RandomState> {
+    table: Table<K, (V, Link<K>), S>,
+    order: Double<K>,
+    capacity: usize,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+impl<K, V> LruCache<K, V, RandomState>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create a new `LruCache` holding at most `capacity` entries.
+    /// A cache with no capacity keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> LruCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Create a new `LruCache` holding at most `capacity` entries,
+    /// hashing keys with `hash_builder`.
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            table: Table::with_capacity_and_hasher(capacity, hash_builder),
+            order: Double::new(),
+            capacity,
+            on_evict: None,
+        }
+    }
+    /// Set a callback receiving every entry evicted to make room.
+    /// Entries taken out with `pop_lru` or `remove` are not passed to it.
+    pub fn set_on_evict<F>(&mut self, on_evict: F)
+    where
+        F: FnMut(K, V) + 'static,
+    {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+    /// Get the value for the given key, marking it as the most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, node) = self.table.get(key)?;
+        self.order.move_node_to_back(node);
+        Ok(value)
+    }
+    /// Get the value for the given key without changing its recency.
+    pub fn peek<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(key).map(|(value, _)| value)
+    }
+    /// Update the value for the given key, marking it as the most recently used.
+    pub fn update<Q>(&mut self, key: &Q) -> Result<&mut V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, node) = self.table.update(key)?;
+        self.order.move_node_to_back(node);
+        Ok(value)
+    }
+    /// Check if the cache contains the key, without changing its recency.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(key)
+    }
+    /// Insert a key-value pair as the most recently used entry, evicting the
+    /// least recently used one if the cache is full.
+    /// If the key was already present, its old value is returned.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Ok((old, node)) = self.table.update(&key) {
+            self.order.move_node_to_back(node);
+            return Some(mem::replace(old, value));
+        }
+        if self.capacity == 0 {
+            self.evicted(key, value);
+            return None;
+        }
+        if self.len() >= self.capacity {
+            self.evict_lru();
+        }
+        let node = self.order.push_back_node(key.clone());
+        self.table.insert(key, (value, node));
+        None
+    }
+    /// Remove and return the least recently used entry.
+    pub fn pop_lru(&mut self) -> Result<(K, V)> {
+        let node = self.order.front_node().ok_or(Error::EmptyTable)?;
+        self.order.unlink_node(&node);
+        let (key, (value, _)) = self
+            .table
+            .remove_entry(RefCell::borrow(&node).get_value())?;
+        Ok((key, value))
+    }
+    /// Get the least recently used entry without changing its recency.
+    pub fn peek_lru(&self) -> Result<(&K, &V)> {
+        let node = self.order.front_node().ok_or(Error::EmptyTable)?;
+        let (key, (value, _)) = self
+            .table
+            .get_key_value(RefCell::borrow(&node).get_value())?;
+        Ok((key, value))
+    }
+    /// Remove the key from the cache, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+    /// Remove the key from the cache, returning the stored key and its value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Result<(K, V)>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (key, (value, node)) = self.table.remove_entry(key)?;
+        self.order.unlink_node(&node);
+        Ok((key, value))
+    }
+    /// Change the capacity of the cache, evicting the least recently used
+    /// entries that no longer fit.
+    pub fn resize(&mut self, capacity: usize) {
+        while self.len() > capacity {
+            self.evict_lru();
+        }
+        self.capacity = capacity;
+    }
+    /// Evict the least recently used entry, if any.
+    fn evict_lru(&mut self) {
+        if let Ok((key, value)) = self.pop_lru() {
+            self.evicted(key, value);
+        }
+    }
+    /// Hand an evicted entry to the eviction callback.
+    fn evicted(&mut self, key: K, value: V) {
+        if let Some(on_evict) = self.on_evict.as_mut() {
+            on_evict(key, value);
+        }
+    }
+    /// Remove every entry from the cache, without calling the eviction callback.
+    pub fn clear(&mut self) {
+        while self.pop_lru().is_ok() {}
+    }
+    /// Get the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+    /// Get the maximum number of entries the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<K, V, S> Debug for LruCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("LruCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_cache_ops() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        assert_eq!(cache.get("a").unwrap(), &1);
+        cache.put("c", 3);
+        assert!(!cache.contains_key("b"));
+        assert_eq!(cache.peek("a").unwrap(), &1);
+        assert_eq!(cache.put("a", 10), Some(1));
+        *cache.update("c").unwrap() += 1;
+        assert_eq!(cache.peek_lru().unwrap(), (&"a", &10));
+        assert_eq!(cache.pop_lru().unwrap(), ("a", 10));
+        assert_eq!(cache.remove("c").unwrap(), 4);
+        assert!(cache.is_empty());
+        assert!(cache.peek_lru().is_err());
+        assert!(cache.pop_lru().is_err());
+    }
+
+    #[test]
+    fn test_lru_cache_peek_keeps_order() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, "one");
+        cache.put(2, "two");
+        assert_eq!(cache.peek(&1).unwrap(), &"one");
+        cache.put(3, "three");
+        assert!(!cache.contains_key(&1));
+        assert!(cache.contains_key(&2));
+        assert_eq!(cache.remove_entry(&2).unwrap(), (2, "two"));
+        assert_eq!(cache.peek_lru().unwrap(), (&3, &"three"));
+    }
+
+    #[test]
+    fn test_lru_cache_eviction() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::new(3);
+        let sink = evicted.clone();
+        cache.set_on_evict(move |key, value| sink.borrow_mut().push((key, value)));
+        for i in 0..5 {
+            cache.put(i, i * 10);
+        }
+        assert_eq!(*evicted.borrow(), vec![(0, 0), (1, 10)]);
+        cache.get(&2).unwrap();
+        cache.resize(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.peek(&2).unwrap(), &20);
+        assert_eq!(evicted.borrow()[2..], [(3, 30), (4, 40)]);
+        cache.pop_lru().unwrap();
+        assert_eq!(evicted.borrow().len(), 4);
+
+        cache.resize(0);
+        cache.put(7, 70);
+        assert!(cache.is_empty());
+        assert_eq!(evicted.borrow().last(), Some(&(7, 70)));
+    }
+
+    #[test]
+    fn test_lru_cache_large() {
+        let mut cache = LruCache::new(1000);
+        for i in 0..10_000 {
+            cache.put(i, i);
+            if i % 2 == 0 {
+                cache.get(&(i / 2)).ok();
+            }
+        }
+        assert_eq!(cache.len(), 1000);
+        assert!(cache.contains_key(&9_999));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,296 @@
+use super::{Error, Result, Table};
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+
+/// `MultiMap` is a hash table mapping each key to one or more values.
+///
+/// Values are kept per key in a `Vec` inside a `Table`, in insertion order.
+/// A key is dropped as soon as its last value is removed, so every stored key
+/// has at least one value.
+pub struct MultiMap<K, V, S = RandomState> {
+    table: Table<K, Vec<V>, S>,
+    len: usize,
+}
+
+impl<K, V> MultiMap<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `MultiMap` with the given number of slots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            table: Table::new(capacity),
+            len: 0,
+        }
+    }
+}
+
+impl<K, V, S> MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `MultiMap` that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            table: Table::with_hasher(hash_builder),
+            len: 0,
+        }
+    }
+    /// Get a reference to the map's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        self.table.hasher()
+    }
+    /// Append a value to the ones stored for the key.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.table.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+    /// Get the values for the given key, in insertion order.
+    pub fn get_all<Q>(&self, key: &Q) -> Result<&[V]>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(key).map(Vec::as_slice)
+    }
+    /// Get the first value inserted for the given key.
+    pub fn get<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_all(key)?.first().ok_or(Error::KeyNotFound)
+    }
+    /// Update the values for the given key in place.
+    pub fn update_all<Q>(&mut self, key: &Q) -> Result<&mut [V]>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.update(key).map(Vec::as_mut_slice)
+    }
+    /// Check if the map holds any value for the key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(key)
+    }
+    /// Check if the value is stored under the key.
+    pub fn contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: PartialEq,
+    {
+        self.get_all(key).is_ok_and(|values| values.contains(value))
+    }
+    /// Get the number of values stored under the key.
+    pub fn count<Q>(&self, key: &Q) -> usize
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_all(key).map_or(0, <[V]>::len)
+    }
+    /// Remove the first occurrence of the value stored under the key.
+    /// The key is removed along with its last value.
+    pub fn remove_one<Q>(&mut self, key: &Q, value: &V) -> Result<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: PartialEq,
+    {
+        let values = self.table.update(key)?;
+        let position = values
+            .iter()
+            .position(|stored| stored == value)
+            .ok_or(Error::KeyNotFound)?;
+        let removed = values.remove(position);
+        if values.is_empty() {
+            self.table.remove(key)?;
+        }
+        self.len -= 1;
+        Ok(removed)
+    }
+    /// Remove the key with all of its values.
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Result<Vec<V>>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.table.remove(key)?;
+        self.len -= values.len();
+        Ok(values)
+    }
+    /// Keep only the pairs for which `f` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let mut len = 0;
+        self.table.retain(|key, values| {
+            values.retain(|value| f(key, value));
+            len += values.len();
+            !values.is_empty()
+        });
+        self.len = len;
+    }
+    /// Iterate over every key-value pair, one pair per value.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+    }
+    /// Iterate over the keys along with all of their values.
+    pub fn iter_all(&self) -> impl Iterator<Item = (&K, &[V])> {
+        self.table
+            .iter()
+            .map(|(key, values)| (key, values.as_slice()))
+    }
+    /// Iterate over the distinct keys of the map.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.table.keys()
+    }
+    /// Iterate over every value of the map.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.table.values().flatten()
+    }
+    /// Get the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Get the number of distinct keys in the map.
+    pub fn keys_len(&self) -> usize {
+        self.table.len()
+    }
+    /// Check if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+/// Default implementation for `MultiMap`.
+impl<K, V, S> Default for MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self {
+            table: Table::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<K, V, S> Debug for MultiMap<K, V, S>
+where
+    K: Hash + Eq + Debug,
+    V: Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter_all()).finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for MultiMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_map_ops() {
+        let mut routes = MultiMap::new(8);
+        routes.insert("/users", "api-1");
+        routes.insert("/users", "api-2");
+        routes.insert("/users", "api-1");
+        routes.insert("/health", "probe");
+        assert_eq!(routes.len(), 4);
+        assert_eq!(routes.keys_len(), 2);
+        assert_eq!(
+            routes.get_all("/users").unwrap(),
+            ["api-1", "api-2", "api-1"]
+        );
+        assert_eq!(routes.get("/users").unwrap(), &"api-1");
+        assert_eq!(routes.count("/users"), 3);
+        assert_eq!(routes.count("/missing"), 0);
+        assert!(routes.contains("/users", &"api-2"));
+
+        assert_eq!(routes.remove_one("/users", &"api-1").unwrap(), "api-1");
+        assert_eq!(routes.get_all("/users").unwrap(), ["api-2", "api-1"]);
+        assert!(routes.remove_one("/users", &"api-9").is_err());
+        assert_eq!(routes.remove_one("/health", &"probe").unwrap(), "probe");
+        assert!(!routes.contains_key("/health"));
+        routes.update_all("/users").unwrap()[0] = "api-3";
+        assert_eq!(routes.remove_all("/users").unwrap(), vec!["api-3", "api-1"]);
+        assert!(routes.is_empty());
+        assert!(routes.remove_all("/users").is_err());
+        assert!(routes.get("/users").is_err());
+    }
+
+    #[test]
+    fn test_multi_map_iteration() {
+        let mut tags: MultiMap<&str, i32> = [
+            ("even", 2),
+            ("odd", 1),
+            ("even", 4),
+            ("odd", 3),
+            ("even", 6),
+        ]
+        .into_iter()
+        .collect();
+        let mut pairs: Vec<_> = tags.iter().map(|(k, v)| (*k, *v)).collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("even", 2),
+                ("even", 4),
+                ("even", 6),
+                ("odd", 1),
+                ("odd", 3)
+            ]
+        );
+        let mut groups: Vec<_> = tags.iter_all().map(|(k, v)| (*k, v.to_vec())).collect();
+        groups.sort();
+        assert_eq!(groups, vec![("even", vec![2, 4, 6]), ("odd", vec![1, 3])]);
+        assert_eq!(tags.values().sum::<i32>(), 16);
+        assert_eq!(tags.keys().count(), 2);
+
+        tags.retain(|_, value| *value > 2);
+        assert_eq!(tags.len(), 3);
+        tags.retain(|key, _| *key == "even");
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags.keys_len(), 1);
+        assert_eq!(format!("{:?}", tags), r#"{"even": [4, 6]}"#);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,324 @@
+use super::{Error, Result};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// Maximum load, in tenths, before the table doubles its capacity.
+const MAX_LOAD: usize = 9;
+/// Capacity used when inserting into a table created with no slots.
+const MIN_CAPACITY: usize = 8;
+
+/// An entry stored inline, along with its distance from the ideal slot.
+#[derive(Clone, Debug)]
+struct Slot<K, V> {
+    key: K,
+    value: V,
+    distance: usize,
+}
+
+/// `OpenTable` is a hash table using open addressing.
+///
+/// Every entry lives inline in a single flat array. Collisions are resolved
+/// with Robin Hood linear probing, and removals use backward-shift deletion,
+/// so the table never needs tombstones.
+///
+/// Keys are hashed with the `BuildHasher` given as `S`, like in `Table`.
+#[derive(Clone, Debug)]
+pub struct OpenTable<K, V, S = RandomState> {
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    hash_builder: S,
+}
+
+impl<K, V> OpenTable<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `OpenTable` with the given capacity.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> OpenTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `OpenTable` that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(0, hash_builder)
+    }
+    /// Create a new `OpenTable` with the given capacity, hashing keys with `hash_builder`.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            slots: Self::empty_slots(capacity),
+            len: 0,
+            hash_builder,
+        }
+    }
+    /// Get a reference to the table's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+    /// Allocate `capacity` empty slots.
+    fn empty_slots(capacity: usize) -> Vec<Option<Slot<K, V>>> {
+        std::iter::repeat_with(|| None).take(capacity).collect()
+    }
+    /// Hash the key and return its ideal index.
+    fn hash<Q>(&self, key: &Q) -> usize
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        (self.hash_builder.hash_one(key) as usize) % self.slots.len()
+    }
+    /// Find the slot holding the key.
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let capacity = self.slots.len();
+        let mut index = self.hash(key);
+        let mut distance = 0;
+        // Robin Hood ordering guarantees the key can't sit past a slot
+        // that is closer to its own ideal position than we are to ours.
+        while let Some(slot) = &self.slots[index] {
+            if slot.distance < distance {
+                break;
+            }
+            if slot.key == *key {
+                return Some(index);
+            }
+            index = (index + 1) % capacity;
+            distance += 1;
+        }
+        None
+    }
+    /// Place a key that is known to be absent, displacing richer entries.
+    fn insert_unique(&mut self, key: K, value: V) {
+        let capacity = self.slots.len();
+        let mut index = self.hash(&key);
+        let mut incoming = Slot {
+            key,
+            value,
+            distance: 0,
+        };
+        loop {
+            match &mut self.slots[index] {
+                None => {
+                    self.slots[index] = Some(incoming);
+                    break;
+                }
+                Some(resident) => {
+                    if resident.distance < incoming.distance {
+                        mem::swap(resident, &mut incoming);
+                    }
+                }
+            }
+            index = (index + 1) % capacity;
+            incoming.distance += 1;
+        }
+        self.len += 1;
+    }
+    /// Insert a new key-value pair into the table.
+    /// If the key is already present, its value is replaced.
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(index) = self.find(&key) {
+            if let Some(slot) = self.slots[index].as_mut() {
+                slot.value = value;
+            }
+            return;
+        }
+        if (self.len + 1) * 10 > self.slots.len() * MAX_LOAD {
+            let new_capacity = (self.slots.len() * 2).max(MIN_CAPACITY);
+            self.rehash(new_capacity);
+        }
+        self.insert_unique(key, value);
+    }
+    /// Get the value for the given key.
+    pub fn get(&self, key: &K) -> Result<&V> {
+        self.find(key)
+            .and_then(|index| self.slots[index].as_ref())
+            .map(|slot| &slot.value)
+            .ok_or(Error::KeyNotFound)
+    }
+    /// Remove the key-value pair from the table.
+    pub fn remove(&mut self, key: &K) -> Result<V> {
+        if self.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        let index = self.find(key).ok_or(Error::KeyNotFound)?;
+        let removed = self.slots[index].take().ok_or(Error::KeyNotFound)?;
+        self.len -= 1;
+
+        // Backward-shift: pull every displaced follower one slot closer
+        // to its ideal position until an empty or well-placed slot.
+        let capacity = self.slots.len();
+        let mut hole = index;
+        let mut next = (hole + 1) % capacity;
+        while let Some(slot) = self.slots[next].as_mut() {
+            if slot.distance == 0 {
+                break;
+            }
+            slot.distance -= 1;
+            self.slots[hole] = self.slots[next].take();
+            hole = next;
+            next = (next + 1) % capacity;
+        }
+        Ok(removed.value)
+    }
+    /// Update the value for the given key.
+    pub fn update(&mut self, key: &K) -> Result<&mut V> {
+        if self.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        match self.find(key) {
+            Some(index) => self.slots[index]
+                .as_mut()
+                .map(|slot| &mut slot.value)
+                .ok_or(Error::KeyNotFound),
+            None => Err(Error::KeyNotFound),
+        }
+    }
+    /// Resize the table to the new capacity.
+    /// The new capacity must be able to hold every stored entry.
+    pub fn resize(&mut self, new_capacity: usize) -> Result<()> {
+        if new_capacity == 0 || new_capacity < self.len {
+            return Err(Error::InvalidCapacity);
+        }
+        self.rehash(new_capacity);
+        Ok(())
+    }
+    /// Move every entry into a fresh array of `new_capacity` slots.
+    fn rehash(&mut self, new_capacity: usize) {
+        let old_slots = mem::replace(&mut self.slots, Self::empty_slots(new_capacity));
+        self.len = 0;
+        for slot in old_slots.into_iter().flatten() {
+            self.insert_unique(slot.key, slot.value);
+        }
+    }
+    /// Get the number of entries in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get the number of slots in the table.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+/// Default implementation for `OpenTable`.
+impl<K, V, S> Default for OpenTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_capacity_and_hasher(64, S::default())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that every entry is reachable from its ideal slot without gaps.
+    fn assert_robin_hood<K, V, S>(table: &OpenTable<K, V, S>)
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let capacity = table.capacity();
+        for (index, slot) in table.slots.iter().enumerate() {
+            if let Some(slot) = slot {
+                let ideal = table.hash(&slot.key);
+                assert_eq!((ideal + slot.distance) % capacity, index);
+                if slot.distance > 0 {
+                    let previous = table.slots[(index + capacity - 1) % capacity].as_ref();
+                    assert!(previous.unwrap().distance + 1 >= slot.distance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_open_table_ops() {
+        let mut table = OpenTable::new(16);
+        table.insert("key1", "value1");
+        assert_eq!(table.get(&"key1").unwrap(), &"value1");
+        table.remove(&"key1").unwrap();
+        assert!(table.get(&"key1").is_err());
+        table.insert("key2", "value2");
+        if let Ok(v) = table.update(&"key2") {
+            *v = "value3";
+        }
+        assert_eq!(table.get(&"key2").unwrap(), &"value3");
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_open_table_probing() {
+        let mut table = OpenTable::new(0);
+        for i in 0..200 {
+            table.insert(i, i * 10);
+        }
+        assert_eq!(table.len(), 200);
+        assert!(table.capacity() * MAX_LOAD >= table.len() * 10);
+        assert_robin_hood(&table);
+        for i in (0..200).step_by(3) {
+            assert_eq!(table.remove(&i).unwrap(), i * 10);
+            assert_robin_hood(&table);
+        }
+        for i in 0..200 {
+            if i % 3 == 0 {
+                assert!(table.get(&i).is_err());
+            } else {
+                assert_eq!(table.get(&i).unwrap(), &(i * 10));
+            }
+        }
+        table.resize(table.len()).unwrap();
+        assert_robin_hood(&table);
+        assert_eq!(table.get(&199).unwrap(), &1990);
+    }
+
+    #[test]
+    fn test_open_table_hashers() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let mut table: OpenTable<i32, i32, BuildHasherDefault<DefaultHasher>> =
+            OpenTable::with_hasher(BuildHasherDefault::default());
+        for i in 0..50 {
+            table.insert(i, -i);
+        }
+        assert_robin_hood(&table);
+        assert_eq!(table.get(&42).unwrap(), &-42);
+    }
+
+    #[test]
+    fn test_open_table_unbounded_elements() {
+        let mut table: OpenTable<String, Box<dyn Fn() -> &'static str>> = OpenTable::new(0);
+        table.insert("a".to_string(), Box::new(|| "a"));
+        table.insert("b".to_string(), Box::new(|| "b"));
+        assert_eq!(table.get(&"b".to_string()).unwrap()(), "b");
+        assert_eq!(table.remove(&"a".to_string()).unwrap()(), "a");
+    }
+
+    #[test]
+    fn test_open_table_errors() {
+        let mut table: OpenTable<&str, &str> = OpenTable::new(16);
+        assert!(table.get(&"key1").is_err());
+        assert!(table.remove(&"key1").is_err());
+        assert!(table.update(&"key1").is_err());
+        assert!(table.resize(0).is_err());
+        table.insert("key1", "value1");
+        table.insert("key2", "value2");
+        assert!(table.resize(1).is_err());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,656 @@
+use super::{Error, Result, Table, TableStats};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::{mem, slice, vec};
+
+/// A stored pair, with the hash of its key kept to rebuild the index cheaply.
+#[derive(Clone)]
+struct Slot<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// Positions of the pairs whose keys share a hash. Keys rarely share all 64
+/// bits, so a single position is stored without allocating.
+#[derive(Clone)]
+enum Positions {
+    One(usize),
+    Many(Vec<usize>),
+}
+
+impl Positions {
+    fn as_slice(&self) -> &[usize] {
+        match self {
+            Positions::One(position) => slice::from_ref(position),
+            Positions::Many(positions) => positions,
+        }
+    }
+    fn as_mut_slice(&mut self) -> &mut [usize] {
+        match self {
+            Positions::One(position) => slice::from_mut(position),
+            Positions::Many(positions) => positions,
+        }
+    }
+    fn push(&mut self, position: usize) {
+        match self {
+            Positions::One(first) => *self = Positions::Many(vec![*first, position]),
+            Positions::Many(positions) => positions.push(position),
+        }
+    }
+    /// Remove a position, returning false if it was the only one.
+    fn remove(&mut self, position: usize) -> bool {
+        let Positions::Many(positions) = self else {
+            return false;
+        };
+        positions.retain(|&p| p != position);
+        if let [last] = positions[..] {
+            *self = Positions::One(last);
+        }
+        true
+    }
+}
+
+/// `OrderedTable` is a hash table that remembers the insertion order of its keys.
+///
+/// Pairs live densely in a vector, in order, and an index `Table` maps the
+/// hash of every key to its position in that vector. Lookups by key are O(1),
+/// and pairs can also be reached, moved and sorted by position.
+///
+/// Keys are hashed with the `BuildHasher` given as `S`, like in `Table`. The
+/// index hashes those hashes again with its own random keys, and reseeds
+/// itself like any `Table` if they still collide.
+#[derive(Clone)]
+pub struct OrderedTable<K, V, S = RandomState> {
+    entries: Vec<Slot<K, V>>,
+    index: Table<u64, Positions>,
+    hash_builder: S,
+}
+
+impl<K, V> OrderedTable<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `OrderedTable` with the given number of index slots,
+    /// rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_index_and_hasher(Table::new(capacity), RandomState::new())
+    }
+    /// Create a new `OrderedTable` able to hold `capacity` elements without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> OrderedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `OrderedTable` that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_index_and_hasher(Table::new(0), hash_builder)
+    }
+    /// Create a new `OrderedTable` able to hold `capacity` elements without growing,
+    /// hashing keys with `hash_builder`.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut table = Self::with_index_and_hasher(Table::with_capacity(capacity), hash_builder);
+        table.entries.reserve(capacity);
+        table
+    }
+    /// Create a new, empty `OrderedTable` around an empty index.
+    fn with_index_and_hasher(index: Table<u64, Positions>, hash_builder: S) -> Self {
+        Self {
+            entries: Vec::new(),
+            index,
+            hash_builder,
+        }
+    }
+    /// Get a reference to the table's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+    /// Hash the key.
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(key)
+    }
+    /// Find the position of the key's pair in the table.
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let positions = self.index.get(&self.hash(key)).ok()?;
+        positions
+            .as_slice()
+            .iter()
+            .copied()
+            .find(|&i| self.entries[i].key.borrow() == key)
+    }
+    /// Add the position of a pair to the index.
+    fn index_position(&mut self, hash: u64, position: usize) {
+        match self.index.update(&hash) {
+            Ok(positions) => positions.push(position),
+            Err(_) => {
+                self.index.insert(hash, Positions::One(position));
+            }
+        }
+    }
+    /// Remove the position of a pair from the index.
+    fn unindex_position(&mut self, hash: u64, position: usize) {
+        if let Ok(positions) = self.index.update(&hash) {
+            if !positions.remove(position) {
+                let _ = self.index.remove(&hash);
+            }
+        }
+    }
+    /// Point the index at the new position of a moved pair.
+    fn move_position(&mut self, hash: u64, from: usize, to: usize) {
+        if let Ok(positions) = self.index.update(&hash) {
+            for position in positions.as_mut_slice() {
+                if *position == from {
+                    *position = to;
+                }
+            }
+        }
+    }
+    /// Point the index back at the current positions of the pairs.
+    fn rebuild_index(&mut self) {
+        self.index.drain().for_each(drop);
+        for i in 0..self.entries.len() {
+            self.index_position(self.entries[i].hash, i);
+        }
+    }
+    /// Insert a new key-value pair at the end of the table.
+    /// If the key is already present, its value is replaced in place and the old one returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(i) = self.find(&key) {
+            return Some(mem::replace(&mut self.entries[i].value, value));
+        }
+        let hash = self.hash(&key);
+        self.index_position(hash, self.entries.len());
+        self.entries.push(Slot { hash, key, value });
+        None
+    }
+    /// Get the value for the given key.
+    pub fn get<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+    /// Get the stored key and the value for the given key.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Result<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key).ok_or(Error::KeyNotFound)?;
+        let slot = &self.entries[i];
+        Ok((&slot.key, &slot.value))
+    }
+    /// Get the position of the given key in the table.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Result<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).ok_or(Error::KeyNotFound)
+    }
+    /// Get the key-value pair at the given position.
+    pub fn get_index(&self, index: usize) -> Result<(&K, &V)> {
+        self.entries
+            .get(index)
+            .map(|slot| (&slot.key, &slot.value))
+            .ok_or(Error::IndexOutOfBounds)
+    }
+    /// Get the key and a mutable reference to the value at the given position.
+    pub fn get_index_mut(&mut self, index: usize) -> Result<(&K, &mut V)> {
+        self.entries
+            .get_mut(index)
+            .map(|slot| (&slot.key, &mut slot.value))
+            .ok_or(Error::IndexOutOfBounds)
+    }
+    /// Check if the table contains the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+    /// Update the value for the given key.
+    pub fn update<Q>(&mut self, key: &Q) -> Result<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(key).ok_or(Error::KeyNotFound)?;
+        Ok(&mut self.entries[i].value)
+    }
+    /// Remove the key-value pair from the table in O(1), moving the last
+    /// pair into its position.
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.entries.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        let i = self.find(key).ok_or(Error::KeyNotFound)?;
+        self.unindex_position(self.entries[i].hash, i);
+        let removed = self.entries.swap_remove(i);
+        if let Some(moved) = self.entries.get(i) {
+            // The former last pair now lives at `i`.
+            self.move_position(moved.hash, self.entries.len(), i);
+        }
+        Ok(removed.value)
+    }
+    /// Remove the key-value pair from the table, shifting every following
+    /// pair one position back to keep the order. This takes O(n).
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.entries.is_empty() {
+            return Err(Error::EmptyTable);
+        }
+        let i = self.find(key).ok_or(Error::KeyNotFound)?;
+        self.unindex_position(self.entries[i].hash, i);
+        let removed = self.entries.remove(i);
+        for positions in self.index.values_mut() {
+            for position in positions.as_mut_slice() {
+                if *position > i {
+                    *position -= 1;
+                }
+            }
+        }
+        Ok(removed.value)
+    }
+    /// Move the pair at position `from` to position `to`, shifting the pairs in between.
+    pub fn move_index(&mut self, from: usize, to: usize) -> Result<()> {
+        let len = self.entries.len();
+        if from >= len || to >= len {
+            return Err(Error::IndexOutOfBounds);
+        }
+        match from.cmp(&to) {
+            Ordering::Less => self.entries[from..=to].rotate_left(1),
+            Ordering::Greater => self.entries[to..=from].rotate_right(1),
+            Ordering::Equal => return Ok(()),
+        }
+        self.rebuild_index();
+        Ok(())
+    }
+    /// Remove the last key-value pair of the table.
+    pub fn pop(&mut self) -> Result<(K, V)> {
+        let slot = self.entries.pop().ok_or(Error::EmptyTable)?;
+        self.unindex_position(slot.hash, self.entries.len());
+        Ok((slot.key, slot.value))
+    }
+    /// Sort the pairs in place with the comparison function.
+    /// The sort is stable.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.entries
+            .sort_by(|a, b| compare(&a.key, &a.value, &b.key, &b.value));
+        self.rebuild_index();
+    }
+    /// Sort the pairs in place with the comparison function, without
+    /// keeping the order of equal pairs.
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.entries
+            .sort_unstable_by(|a, b| compare(&a.key, &a.value, &b.key, &b.value));
+        self.rebuild_index();
+    }
+    /// Sort the pairs in place by key.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| k1.cmp(k2));
+    }
+    /// Iterate over the key-value pairs of the table, in order.
+    pub fn iter(&self) -> OrderedIter<'_, K, V> {
+        OrderedIter {
+            inner: self.entries.iter(),
+        }
+    }
+    /// Iterate over the key-value pairs of the table with mutable values, in order.
+    pub fn iter_mut(&mut self) -> OrderedIterMut<'_, K, V> {
+        OrderedIterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+    /// Iterate over the keys of the table, in order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.iter().map(|(key, _)| key)
+    }
+    /// Iterate over the values of the table, in order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, value)| value)
+    }
+    /// Iterate over mutable references to the values of the table, in order.
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.iter_mut().map(|(_, value)| value)
+    }
+    /// Gather statistics about how the hash index is filled.
+    pub fn stats(&self) -> TableStats {
+        self.index.stats()
+    }
+    /// Get the number of elements in the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+/// Default implementation for `OrderedTable`.
+impl<K, V, S> Default for OrderedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_index_and_hasher(Table::default(), S::default())
+    }
+}
+
+impl<K, V, S> Debug for OrderedTable<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map()
+            .entries(self.entries.iter().map(|slot| (&slot.key, &slot.value)))
+            .finish()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for OrderedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut table = Self::with_hasher(S::default());
+        table.extend(iter);
+        table
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for OrderedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> IntoIterator for OrderedTable<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = OrderedIntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        OrderedIntoIter {
+            inner: self.entries.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a OrderedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = OrderedIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the key-value pairs of an `OrderedTable`, in order,
+/// created by `OrderedTable::iter`.
+pub struct OrderedIter<'a, K, V> {
+    inner: slice::Iter<'a, Slot<K, V>>,
+}
+
+impl<'a, K, V> Iterator for OrderedIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|slot| (&slot.key, &slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for OrderedIter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|slot| (&slot.key, &slot.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for OrderedIter<'_, K, V> {}
+
+/// Iterator over the key-value pairs of an `OrderedTable` with mutable values,
+/// in order, created by `OrderedTable::iter_mut`.
+pub struct OrderedIterMut<'a, K, V> {
+    inner: slice::IterMut<'a, Slot<K, V>>,
+}
+
+impl<'a, K, V> Iterator for OrderedIterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|slot| (&slot.key, &mut slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for OrderedIterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|slot| (&slot.key, &mut slot.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for OrderedIterMut<'_, K, V> {}
+
+/// Owning iterator over the key-value pairs of an `OrderedTable`, in order.
+pub struct OrderedIntoIter<K, V> {
+    inner: vec::IntoIter<Slot<K, V>>,
+}
+
+impl<K, V> Iterator for OrderedIntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|slot| (slot.key, slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for OrderedIntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|slot| (slot.key, slot.value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for OrderedIntoIter<K, V> {}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    fn keys<S: BuildHasher>(table: &OrderedTable<&'static str, i32, S>) -> Vec<&'static str> {
+        table.keys().copied().collect()
+    }
+
+    #[test]
+    fn test_ordered_table_ops() {
+        let mut table = OrderedTable::new(0);
+        for (i, key) in ["name", "version", "edition", "license"].iter().enumerate() {
+            assert_eq!(table.insert(*key, i as i32), None);
+        }
+        assert_eq!(table.insert("version", 10), Some(1));
+        assert_eq!(keys(&table), vec!["name", "version", "edition", "license"]);
+        assert_eq!(table.get("version").unwrap(), &10);
+        assert_eq!(table.get_index(2).unwrap(), (&"edition", &2));
+        assert_eq!(table.get_index_of("license").unwrap(), 3);
+        assert!(table.get_index(4).is_err());
+        assert!(table.get_index_of("authors").is_err());
+        *table.update("name").unwrap() += 100;
+        *table.get_index_mut(0).unwrap().1 += 1;
+        assert_eq!(table.get("name").unwrap(), &101);
+        assert_eq!(
+            format!("{:?}", table),
+            r#"{"name": 101, "version": 10, "edition": 2, "license": 3}"#
+        );
+        assert_eq!(table.iter().next_back().unwrap(), (&"license", &3));
+    }
+
+    #[test]
+    fn test_ordered_table_removal() {
+        let mut table: OrderedTable<&str, i32> = [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)]
+            .into_iter()
+            .collect();
+        assert_eq!(table.swap_remove("b").unwrap(), 2);
+        assert_eq!(keys(&table), vec!["a", "e", "c", "d"]);
+        assert_eq!(table.get_index_of("e").unwrap(), 1);
+        assert_eq!(table.shift_remove("a").unwrap(), 1);
+        assert_eq!(keys(&table), vec!["e", "c", "d"]);
+        assert_eq!(table.get_index_of("d").unwrap(), 2);
+        assert_eq!(table.get("c").unwrap(), &3);
+        assert!(table.swap_remove("a").is_err());
+        assert_eq!(table.swap_remove("d").unwrap(), 4);
+        assert_eq!(table.pop().unwrap(), ("c", 3));
+        assert_eq!(table.get("e").unwrap(), &5);
+        assert!(!table.contains_key("c"));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_ordered_table_reorder() {
+        let mut table: OrderedTable<&str, i32> = [("d", 1), ("b", 4), ("a", 3), ("c", 2)]
+            .into_iter()
+            .collect();
+        table.move_index(0, 3).unwrap();
+        assert_eq!(keys(&table), vec!["b", "a", "c", "d"]);
+        table.move_index(2, 0).unwrap();
+        assert_eq!(keys(&table), vec!["c", "b", "a", "d"]);
+        assert!(table.move_index(0, 4).is_err());
+        assert_eq!(table.get_index_of("c").unwrap(), 0);
+
+        table.sort_keys();
+        assert_eq!(keys(&table), vec!["a", "b", "c", "d"]);
+        assert_eq!(table.get_index_of("d").unwrap(), 3);
+        table.sort_unstable_by(|_, v1, _, v2| v1.cmp(v2));
+        assert_eq!(keys(&table), vec!["d", "c", "a", "b"]);
+        assert_eq!(table.get("a").unwrap(), &3);
+
+        for value in table.values_mut() {
+            *value *= 10;
+        }
+        let pairs: Vec<_> = table.into_iter().collect();
+        assert_eq!(pairs, vec![("d", 10), ("c", 20), ("a", 30), ("b", 40)]);
+    }
+
+    #[test]
+    fn test_ordered_table_growth() {
+        let mut table = OrderedTable::with_capacity(4);
+        for i in 0..500 {
+            table.insert(i, i * 2);
+        }
+        for i in (0..500).step_by(2) {
+            table.swap_remove(&i).unwrap();
+        }
+        for i in (1..500).step_by(2) {
+            let position = table.get_index_of(&i).unwrap();
+            assert_eq!(table.get_index(position).unwrap(), (&i, &(i * 2)));
+        }
+        assert_eq!(table.len(), 250);
+        assert_eq!(table.values().count(), 250);
+    }
+
+    #[test]
+    fn test_ordered_table_index() {
+        let mut table = OrderedTable::new(0);
+        for i in 0..1000 {
+            table.insert(i, i);
+        }
+        let stats = table.stats();
+        assert_eq!(stats.len, 1000);
+        assert!(stats.max_probe_length >= 1);
+    }
+
+    #[test]
+    fn test_ordered_table_hash_collisions() {
+        /// Hasher giving every key the same hash.
+        #[derive(Default)]
+        struct ConstantHasher;
+
+        impl Hasher for ConstantHasher {
+            fn finish(&self) -> u64 {
+                0
+            }
+            fn write(&mut self, _bytes: &[u8]) {}
+        }
+
+        let mut table = OrderedTable::with_hasher(BuildHasherDefault::<ConstantHasher>::default());
+        for i in 0..10 {
+            table.insert(i, i * 10);
+        }
+        assert_eq!(table.stats().len, 1);
+        assert_eq!(table.swap_remove(&2).unwrap(), 20);
+        assert_eq!(table.shift_remove(&0).unwrap(), 0);
+        assert_eq!(table.pop().unwrap(), (8, 80));
+        table.move_index(0, 6).unwrap();
+        let keys: Vec<_> = table.keys().copied().collect();
+        assert_eq!(keys, vec![9, 3, 4, 5, 6, 7, 1]);
+        for (position, key) in keys.iter().enumerate() {
+            assert_eq!(table.get_index_of(key).unwrap(), position);
+            assert_eq!(table.get(key).unwrap(), &(key * 10));
+        }
+        while table.pop().is_ok() {}
+        assert_eq!(table.stats().len, 0);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,474 @@
+use super::snapshot::{file_header, read_file, write_file, MAX_PREALLOCATED};
+use super::{ByteSink, Codec, Error, Result};
+use std::cmp::Reverse;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::io::{Read, Write};
+
+/// Bytes every perfect map file starts with.
+const MAGIC: [u8; 4] = *b"RDPH";
+/// Version of the file format written by `write_to`.
+const VERSION: u16 = 1;
+/// Average number of keys per bucket. Fewer buckets make smaller files, more
+/// make builds faster.
+const BUCKET_SIZE: usize = 4;
+/// Keys per spare slot. Hashing into a few more slots than keys keeps the
+/// last buckets from searching for the very last free slots.
+const KEYS_PER_SPARE_SLOT: usize = 100;
+/// Number of `d0` values tried for a bucket before drawing a new seed.
+const MAX_D0: u32 = 32;
+/// Seeds tried by `build` before it gives up. Distinct keys almost always
+/// fit under the first one.
+const MAX_SEEDS: usize = 32;
+/// FNV-1a 64-bit offset basis and prime.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The `(d0, d1)` pair that places the keys of a bucket.
+type Displacement = (u32, u32);
+
+/// Step a splitmix64 generator, used to draw the seeds tried by `build`.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    mix(*state)
+}
+
+/// Scramble the bits of a hash (the splitmix64 finalizer).
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Two FNV-1a states, seeded apart, fed the `Codec` bytes of a key as they
+/// are encoded, so hashing a key allocates nothing. Unlike `RandomState` it
+/// gives the same result in every process, so a map written to a file can be
+/// loaded later.
+struct KeyHasher {
+    first: u64,
+    second: u64,
+}
+
+impl KeyHasher {
+    fn new(seed: u64) -> Self {
+        Self {
+            first: FNV_OFFSET ^ seed,
+            second: FNV_OFFSET ^ !seed,
+        }
+    }
+}
+
+impl ByteSink for KeyHasher {
+    fn put(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.first = (self.first ^ byte as u64).wrapping_mul(FNV_PRIME);
+            self.second = (self.second ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Encode a key to the bytes it is hashed from.
+fn encode<K: Codec>(key: &K) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    key.encode(&mut bytes);
+    bytes
+}
+
+/// Add `d1` to a slot below `slots`, wrapping around without a division.
+fn shift(slot: usize, d1: usize, slots: usize) -> usize {
+    match d1 < slots - slot {
+        true => slot + d1,
+        false => d1 - (slots - slot),
+    }
+}
+
+/// Get the index of a slot among `0..len`, remapping the spare slots past it.
+fn compress(slot: usize, len: usize, remap: &[u32]) -> usize {
+    match slot.checked_sub(len) {
+        Some(spare) => remap[spare] as usize,
+        None => slot,
+    }
+}
+
+/// The hashes of a key under a seed: its bucket, and the two values its
+/// bucket's displacement combines into a slot.
+struct KeyHash {
+    bucket: usize,
+    f1: u64,
+    f2: u64,
+}
+
+impl KeyHash {
+    fn new<K: Codec>(key: &K, seed: u64, buckets: usize, slots: usize) -> Self {
+        let mut hasher = KeyHasher::new(seed);
+        key.encode(&mut hasher);
+        let first = mix(hasher.first);
+        let second = mix(hasher.second);
+        Self {
+            bucket: (first % buckets as u64) as usize,
+            f1: second % slots as u64,
+            f2: (second >> 32) % slots as u64,
+        }
+    }
+    /// Get the slot of the key under the displacement of its bucket.
+    /// `d0` is below `MAX_D0` and `d1` below `slots`, so nothing overflows.
+    fn slot(&self, (d0, d1): Displacement, slots: usize) -> usize {
+        ((self.f1 + d0 as u64 * self.f2 + d1 as u64) % slots as u64) as usize
+    }
+}
+
+/// `PerfectMap` is a read-only map built with a minimal perfect hash function.
+///
+/// `build` uses CHD (compress, hash and displace): keys are hashed into small
+/// buckets, and each bucket, largest first, gets the first displacement
+/// `(d0, d1)` that moves all of its keys to free slots, at
+/// `(f1 + d0 * f2 + d1) % slots`. There are 1% more slots than keys, and the
+/// keys placed past the first `n` are remapped to the free slots below it, so
+/// every key gets its own index among exactly `n`. A lookup hashes the key
+/// once and reads one slot with no collisions.
+///
+/// Keys are hashed from their `Codec` bytes with a fixed hash function, so the
+/// map can be written with `write_to` and loaded with `read_from` without
+/// rebuilding it. Each slot stores its key, and a lookup compares it, so keys
+/// outside the build set fail with `KeyNotFound` instead of reading a wrong
+/// value.
+pub struct PerfectMap<K, V> {
+    seed: u64,
+    displacements: Vec<Displacement>,
+    /// The index each spare slot is remapped to, if a key was placed in it.
+    remap: Vec<u32>,
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> PerfectMap<K, V>
+where
+    K: Eq + Codec,
+    V: Codec,
+{
+    /// Build a map from a set of pairs.
+    /// Fails with `KeyExists` if two keys encode to the same bytes, and with
+    /// `TooManyCollisions` if no seed tried places every key.
+    pub fn build<I>(pairs: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let pairs: Vec<(K, V)> = pairs.into_iter().collect();
+        if pairs.len() > u32::MAX as usize {
+            return Err(Error::InvalidCapacity);
+        }
+        // Keys with the same bytes hash alike under every seed, so they are
+        // rejected before searching for one.
+        let mut encoded: Vec<Vec<u8>> = pairs.iter().map(|(key, _)| encode(key)).collect();
+        encoded.sort_unstable();
+        if encoded.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(Error::KeyExists);
+        }
+        drop(encoded);
+
+        let len = pairs.len();
+        let slots = len + len.div_ceil(KEYS_PER_SPARE_SLOT);
+        let buckets = len.div_ceil(BUCKET_SIZE).max(1);
+        let keys: Vec<&K> = pairs.iter().map(|(key, _)| key).collect();
+        let (seed, displacements, positions) = Self::search(&keys, buckets, slots)?;
+
+        // As many slots below `len` are free as keys were placed past it.
+        let mut taken = vec![false; slots];
+        positions.iter().for_each(|&slot| taken[slot] = true);
+        let mut free = (0..len).filter(|&slot| !taken[slot]);
+        let remap: Vec<u32> = (len..slots)
+            .map(|slot| match taken[slot] {
+                true => free.next().expect("a free slot for every spare one taken") as u32,
+                false => 0,
+            })
+            .collect();
+        let mut entries: Vec<Option<(K, V)>> = pairs.iter().map(|_| None).collect();
+        for (pair, slot) in pairs.into_iter().zip(positions) {
+            entries[compress(slot, len, &remap)] = Some(pair);
+        }
+        Ok(Self {
+            seed,
+            displacements,
+            remap,
+            entries: entries
+                .into_iter()
+                .map(|pair| pair.expect("every index is taken"))
+                .collect(),
+        })
+    }
+    /// Find a seed under which every bucket fits, trying `MAX_SEEDS` of them.
+    /// Returns the seed, the displacements and the slot of each key.
+    fn search(
+        keys: &[&K],
+        buckets: usize,
+        slots: usize,
+    ) -> Result<(u64, Vec<Displacement>, Vec<usize>)> {
+        let mut state = 0;
+        (0..MAX_SEEDS)
+            .find_map(|_| {
+                let seed = splitmix64(&mut state);
+                let (displacements, positions) = Self::displace(keys, seed, buckets, slots)?;
+                Some((seed, displacements, positions))
+            })
+            .ok_or(Error::TooManyCollisions)
+    }
+    /// Find a displacement for every bucket under `seed`.
+    /// Returns the displacements and the slot of each key, or `None` if a
+    /// bucket doesn't fit.
+    fn displace(
+        keys: &[&K],
+        seed: u64,
+        buckets: usize,
+        slots: usize,
+    ) -> Option<(Vec<Displacement>, Vec<usize>)> {
+        let hashes: Vec<KeyHash> = keys
+            .iter()
+            .map(|key| KeyHash::new(*key, seed, buckets, slots))
+            .collect();
+        let mut members = vec![Vec::new(); buckets];
+        for (index, hash) in hashes.iter().enumerate() {
+            members[hash.bucket].push(index);
+        }
+        let mut order: Vec<usize> = (0..buckets).collect();
+        order.sort_unstable_by_key(|&bucket| Reverse(members[bucket].len()));
+
+        let mut taken = vec![false; slots];
+        let mut displacements = vec![(0, 0); buckets];
+        let mut positions = vec![0; keys.len()];
+        for bucket in order {
+            let members = &members[bucket];
+            if members.is_empty() {
+                break;
+            }
+            // Under one `d0`, every `d1` shifts the bucket's slots by one, so
+            // a bucket of one key always finds the last free slot.
+            let displacement = (0..MAX_D0).find_map(|d0| {
+                let bases: Vec<usize> = members
+                    .iter()
+                    .map(|&index| hashes[index].slot((d0, 0), slots))
+                    .collect();
+                // Keys sharing a base collide under every `d1`.
+                if (1..bases.len()).any(|i| bases[..i].contains(&bases[i])) {
+                    return None;
+                }
+                let d1 = (0..slots)
+                    .find(|&d1| bases.iter().all(|&base| !taken[shift(base, d1, slots)]))?;
+                Some((d0, d1 as u32))
+            })?;
+            displacements[bucket] = displacement;
+            for &index in members {
+                let slot = hashes[index].slot(displacement, slots);
+                taken[slot] = true;
+                positions[index] = slot;
+            }
+        }
+        Some((displacements, positions))
+    }
+    /// Get the index a key would be stored at.
+    fn index(&self, key: &K) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let slots = self.entries.len() + self.remap.len();
+        let hash = KeyHash::new(key, self.seed, self.displacements.len(), slots);
+        let slot = hash.slot(self.displacements[hash.bucket], slots);
+        Some(compress(slot, self.entries.len(), &self.remap))
+    }
+    /// Get the index of the key among `0..len()`, unique to every key of the map.
+    pub fn index_of(&self, key: &K) -> Result<usize> {
+        match self.index(key) {
+            Some(index) if self.entries[index].0 == *key => Ok(index),
+            _ => Err(Error::KeyNotFound),
+        }
+    }
+    /// Get the value for the given key.
+    pub fn get(&self, key: &K) -> Result<&V> {
+        self.get_key_value(key).map(|(_, value)| value)
+    }
+    /// Get the stored key and the value for the given key.
+    pub fn get_key_value(&self, key: &K) -> Result<(&K, &V)> {
+        let (key, value) = &self.entries[self.index_of(key)?];
+        Ok((key, value))
+    }
+    /// Check if the map contains the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index_of(key).is_ok()
+    }
+    /// Iterate over the pairs of the map, in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+    /// Get the number of pairs in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Check if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Write the map to `writer`: the seed, the displacements, the remapped
+    /// spare slots, then the pairs in index order, between a header and a
+    /// checksum.
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<()> {
+        let mut buf = file_header(MAGIC, VERSION);
+        self.seed.encode(&mut buf);
+        self.displacements.len().encode(&mut buf);
+        self.remap.len().encode(&mut buf);
+        self.entries.len().encode(&mut buf);
+        for &(d0, d1) in &self.displacements {
+            d0.encode(&mut buf);
+            d1.encode(&mut buf);
+        }
+        self.remap.iter().for_each(|index| index.encode(&mut buf));
+        for (key, value) in &self.entries {
+            key.encode(&mut buf);
+            value.encode(&mut buf);
+        }
+        write_file(buf, writer)
+    }
+    /// Load a map written by `write_to`, without rebuilding it.
+    /// Fails with `CorruptSnapshot` on truncated, altered or unknown input.
+    pub fn read_from(reader: impl Read) -> Result<Self> {
+        let buf = read_file(reader, MAGIC, VERSION)?;
+        let mut input = buf.as_slice();
+        let seed = u64::decode(&mut input)?;
+        let buckets = usize::decode(&mut input)?;
+        let spare = usize::decode(&mut input)?;
+        let len = usize::decode(&mut input)?;
+        // Every displacement, index and pair takes at least one byte, so
+        // larger counts are corrupt. Pairs can take far more memory than
+        // their bytes, so their count only preallocates up to a cap.
+        if buckets == 0 || buckets.saturating_add(spare).saturating_add(len) > input.len() {
+            return Err(Error::CorruptSnapshot);
+        }
+        let mut displacements = Vec::with_capacity(buckets);
+        for _ in 0..buckets {
+            let displacement = (u32::decode(&mut input)?, u32::decode(&mut input)?);
+            if displacement.0 >= MAX_D0 || displacement.1 as usize >= (len + spare).max(1) {
+                return Err(Error::CorruptSnapshot);
+            }
+            displacements.push(displacement);
+        }
+        let mut remap = Vec::with_capacity(spare);
+        for _ in 0..spare {
+            match u32::decode(&mut input)? {
+                index if (index as usize) < len => remap.push(index),
+                _ => return Err(Error::CorruptSnapshot),
+            }
+        }
+        let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATED));
+        for _ in 0..len {
+            entries.push((K::decode(&mut input)?, V::decode(&mut input)?));
+        }
+        if !input.is_empty() {
+            return Err(Error::CorruptSnapshot);
+        }
+        let map = Self {
+            seed,
+            displacements,
+            remap,
+            entries,
+        };
+        // Each key must hash back to its own index, or lookups would miss it.
+        let misplaced = map
+            .entries
+            .iter()
+            .enumerate()
+            .any(|(index, (key, _))| map.index(key) != Some(index));
+        match misplaced {
+            true => Err(Error::CorruptSnapshot),
+            false => Ok(map),
+        }
+    }
+}
+
+impl<K, V> Debug for PerfectMap<K, V>
+where
+    K: Eq + Codec + Debug,
+    V: Codec + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::super::snapshot::crc32;
+    use super::*;
+
+    fn words(n: u32) -> PerfectMap<String, u32> {
+        PerfectMap::build((0..n).map(|i| (format!("word{}", i), i))).unwrap()
+    }
+
+    #[test]
+    fn test_perfect_map_build() {
+        for n in [1, 2, 7, 100, 5000] {
+            let map = words(n);
+            assert_eq!(map.len(), n as usize);
+            let mut indices: Vec<usize> = (0..n)
+                .map(|i| map.index_of(&format!("word{}", i)).unwrap())
+                .collect();
+            indices.sort();
+            assert_eq!(indices, (0..n as usize).collect::<Vec<_>>());
+            for i in 0..n {
+                assert_eq!(map.get(&format!("word{}", i)).unwrap(), &i);
+            }
+            // Keys outside the build set land on some slot, but never match it.
+            for i in n..n + 100 {
+                assert!(matches!(
+                    map.get(&format!("word{}", i)),
+                    Err(Error::KeyNotFound)
+                ));
+            }
+        }
+
+        let empty: PerfectMap<u64, u64> = PerfectMap::build([]).unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.get(&1).is_err());
+        assert!(matches!(
+            PerfectMap::build([(1u8, 'a'), (2, 'b'), (1, 'c')]),
+            Err(Error::KeyExists)
+        ));
+
+        // Keys hashing alike never fit: the search gives up instead of spinning.
+        let same = 7u64;
+        assert!(matches!(
+            PerfectMap::<u64, u64>::search(&[&1, &same, &same], 1, 4),
+            Err(Error::TooManyCollisions)
+        ));
+    }
+
+    #[test]
+    fn test_perfect_map_file() {
+        let map = words(1000);
+        let mut file = Vec::new();
+        map.write_to(&mut file).unwrap();
+        let loaded: PerfectMap<String, u32> = PerfectMap::read_from(file.as_slice()).unwrap();
+        assert_eq!(loaded.len(), 1000);
+        assert_eq!(loaded.get(&"word999".to_string()).unwrap(), &999);
+        assert!(loaded.get(&"word1000".to_string()).is_err());
+        assert!(loaded.iter().eq(map.iter()));
+
+        let read = |bytes: &[u8]| PerfectMap::<String, u32>::read_from(bytes);
+        assert!(matches!(
+            read(&file[..file.len() - 1]),
+            Err(Error::CorruptSnapshot)
+        ));
+        let mut altered = file.clone();
+        altered[20] ^= 1;
+        assert!(matches!(read(&altered), Err(Error::CorruptSnapshot)));
+        // A valid checksum over a different seed still can't misplace keys.
+        let mut altered = file[..file.len() - 4].to_vec();
+        altered[6] ^= 1;
+        altered.extend_from_slice(&crc32(&altered).to_le_bytes());
+        assert!(matches!(read(&altered), Err(Error::CorruptSnapshot)));
+
+        let empty: PerfectMap<u8, u8> = PerfectMap::build([]).unwrap();
+        let mut file = Vec::new();
+        empty.write_to(&mut file).unwrap();
+        assert!(PerfectMap::<u8, u8>::read_from(file.as_slice())
+            .unwrap()
+            .is_empty());
+    }
+}
+// endregion: --- Tests
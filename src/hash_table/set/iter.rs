@@ -0,0 +1,131 @@
+//! Iterators over the values of a `Set` and over the results of set algebra.
+use super::Set;
+use std::hash::{BuildHasher, Hash};
+use std::iter::{Chain, Flatten};
+use std::{slice, vec};
+
+/// Iterator over the values of a `Set`, created by `Set::iter`.
+pub struct SetIter<'a, T> {
+    pub(super) inner: Flatten<slice::Iter<'a, Option<T>>>,
+    pub(super) remaining: usize,
+}
+
+impl<'a, T> Iterator for SetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for SetIter<'_, T> {}
+
+/// Owning iterator over the values of a `Set`.
+pub struct SetIntoIter<T> {
+    pub(super) inner: Flatten<vec::IntoIter<Option<T>>>,
+    pub(super) remaining: usize,
+}
+
+impl<T> Iterator for SetIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.inner.next()?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for SetIntoIter<T> {}
+
+/// Lazy iterator over the values in either of two sets, created by `Set::union`.
+pub struct Union<'a, T, S> {
+    pub(super) inner: Chain<SetIter<'a, T>, Difference<'a, T, S>>,
+}
+
+impl<'a, T, S> Iterator for Union<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// Lazy iterator over the values in both of two sets, created by `Set::intersection`.
+pub struct Intersection<'a, T, S> {
+    pub(super) inner: SetIter<'a, T>,
+    pub(super) other: &'a Set<T, S>,
+}
+
+impl<'a, T, S> Iterator for Intersection<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.inner.find(|value| other.contains(*value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// Lazy iterator over the values in one set but not in another,
+/// created by `Set::difference`.
+pub struct Difference<'a, T, S> {
+    pub(super) inner: SetIter<'a, T>,
+    pub(super) other: &'a Set<T, S>,
+}
+
+impl<'a, T, S> Iterator for Difference<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.inner.find(|value| !other.contains(*value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// Lazy iterator over the values in exactly one of two sets,
+/// created by `Set::symmetric_difference`.
+pub struct SymmetricDifference<'a, T, S> {
+    pub(super) inner: Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+}
+
+impl<'a, T, S> Iterator for SymmetricDifference<'a, T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
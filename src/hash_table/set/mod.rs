@@ -0,0 +1,550 @@
+mod iter;
+
+use super::swiss::{
+    fresh_seed, seeded_hash, slot_count, RawTable, DEFAULT_MAX_LOAD_FACTOR, GROUP_WIDTH,
+    MAX_PROBE_GROUPS,
+};
+use super::{Error, Result};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+pub use iter::{Difference, Intersection, SetIntoIter, SetIter, SymmetricDifference, Union};
+
+/// `Set` is a hash set built on the same hashing core as `Table`.
+///
+/// It stores unique values, hashed with the `BuildHasher` given as `S`, and
+/// supports the usual set algebra through lazy iterators and operators.
+///
+/// Values sit alone in the control-byte slot array of `Table`, probed a
+/// group of 8 slots at a time. The set grows once it would cross a load
+/// factor of 0.75, rebuilding itself at once, and like `Table` it picks a
+/// fresh seed and rebuilds itself if an insert probes too far.
+#[derive(Clone)]
+pub struct Set<T, S = RandomState> {
+    elements: RawTable<T>,
+    /// Seed mixed into every hash, or 0 until the set is first reseeded.
+    seed: u64,
+    /// Number of times the set was rebuilt with a fresh seed.
+    reseeds: usize,
+    /// Length of the set when it was last reseeded.
+    reseed_len: usize,
+    hash_builder: S,
+}
+
+impl<T> Set<T, RandomState>
+where
+    T: Hash + Eq,
+{
+    /// Create a new `Set` with the given number of slots,
+    /// rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_slots_and_hasher(slot_count(capacity), RandomState::new())
+    }
+    /// Create a new `Set` able to hold `capacity` values without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+/// Get the number of values `slots` slots hold under the maximum load factor.
+fn capacity_of(slots: usize) -> usize {
+    (slots as f64 * DEFAULT_MAX_LOAD_FACTOR).floor() as usize
+}
+
+/// Get the number of slots needed to hold `len` values.
+fn slots_for(len: usize) -> usize {
+    let mut slots = slot_count((len as f64 / DEFAULT_MAX_LOAD_FACTOR).ceil() as usize);
+    while capacity_of(slots) < len {
+        slots *= 2;
+    }
+    slots
+}
+
+impl<T, S> Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `Set` that hashes values with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_slots_and_hasher(0, hash_builder)
+    }
+    /// Create a new `Set` able to hold `capacity` values without growing,
+    /// hashing values with `hash_builder`.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self::with_slots_and_hasher(slots_for(capacity), hash_builder)
+    }
+    /// Create a new `Set` with `slots` slots, hashing values with `hash_builder`.
+    fn with_slots_and_hasher(slots: usize, hash_builder: S) -> Self {
+        Self {
+            elements: RawTable::with_slots(slots),
+            seed: 0,
+            reseeds: 0,
+            reseed_len: 0,
+            hash_builder,
+        }
+    }
+    /// Get a reference to the set's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+    /// Hash the value.
+    fn hash<Q>(&self, value: &Q) -> u64
+    where
+        T: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        seeded_hash(&self.hash_builder, self.seed, value)
+    }
+    /// Find the slot holding the value.
+    fn find<Q>(&self, value: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        self.elements
+            .find(self.hash(value), |stored| stored.borrow() == value)
+    }
+    /// Add a value to the set.
+    /// Returns true if the value was not already present.
+    pub fn insert(&mut self, value: T) -> bool {
+        let mut hash = self.hash(&value);
+        if self
+            .elements
+            .find(hash, |stored| *stored == value)
+            .is_some()
+        {
+            return false;
+        }
+        let slots = self.elements.len();
+        let crowded = self.elements.items + self.elements.tombstones + 1 > self.capacity();
+        if self.len() + 1 > self.capacity() || (crowded && self.len() > self.capacity() / 2) {
+            self.rehash((slots * 2).max(slots_for(self.len() + 1)));
+        } else if crowded {
+            // Too many tombstones lengthen every probe: rebuild at the same size.
+            self.rehash(slots);
+        }
+        let (mut index, probed) = self.elements.find_free(hash);
+        // Reseed at most once per doubling in length, as `Table` does.
+        if probed > MAX_PROBE_GROUPS && self.len() >= 2 * self.reseed_len {
+            self.seed = fresh_seed();
+            self.reseeds += 1;
+            self.reseed_len = self.len();
+            self.rehash(self.elements.len());
+            hash = self.hash(&value);
+            index = self.elements.find_free(hash).0;
+        }
+        self.elements.insert_at(index, hash, value);
+        true
+    }
+    /// Move every value into `slots` fresh slots, right away.
+    fn rehash(&mut self, slots: usize) {
+        let old = mem::replace(&mut self.elements, RawTable::with_slots(slots));
+        for value in old.slots.into_iter().flatten() {
+            let hash = seeded_hash(&self.hash_builder, self.seed, &value);
+            self.elements.insert(hash, value);
+        }
+    }
+    /// Get the number of times the set was rebuilt with a fresh seed after
+    /// an insert probed too far.
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+    /// Check if the set contains the value.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(value).is_some()
+    }
+    /// Get the stored value equal to the given one.
+    pub fn get<Q>(&self, value: &Q) -> Result<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(value).ok_or(Error::KeyNotFound)?;
+        Ok(self.elements.slots[index]
+            .as_ref()
+            .expect("found slot is full"))
+    }
+    /// Remove the value from the set, returning the stored one.
+    pub fn remove<Q>(&mut self, value: &Q) -> Result<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(value).ok_or(Error::KeyNotFound)?;
+        Ok(self.elements.take(index))
+    }
+    /// Get the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.elements.items
+    }
+    /// Check if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Get the number of values the set can hold without growing.
+    pub fn capacity(&self) -> usize {
+        capacity_of(self.elements.len())
+    }
+    /// Grow the set so it can hold `additional` more values without growing again.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        if needed > self.capacity() {
+            self.rehash(slots_for(needed));
+        }
+    }
+    /// Shrink the set to the fewest slots that keep it under the maximum load factor.
+    pub fn shrink_to_fit(&mut self) {
+        let slots = slots_for(self.len()).max(GROUP_WIDTH);
+        if slots < self.elements.len() {
+            self.rehash(slots);
+        }
+    }
+    /// Iterate over the values of the set.
+    pub fn iter(&self) -> SetIter<'_, T> {
+        SetIter {
+            inner: self.elements.slots.iter().flatten(),
+            remaining: self.len(),
+        }
+    }
+    /// Keep only the values for which `f` returns true.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        for index in 0..self.elements.len() {
+            let keep = self.elements.slots[index].as_ref().is_none_or(&mut f);
+            if !keep {
+                self.elements.take(index);
+            }
+        }
+    }
+    /// Iterate lazily over the values in `self`, `other` or both.
+    pub fn union<'a>(&'a self, other: &'a Set<T, S>) -> Union<'a, T, S> {
+        Union {
+            inner: self.iter().chain(other.difference(self)),
+        }
+    }
+    /// Iterate lazily over the values in both `self` and `other`.
+    pub fn intersection<'a>(&'a self, other: &'a Set<T, S>) -> Intersection<'a, T, S> {
+        // Walk the smaller set and probe the larger one.
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Intersection {
+            inner: small.iter(),
+            other: large,
+        }
+    }
+    /// Iterate lazily over the values in `self` but not in `other`.
+    pub fn difference<'a>(&'a self, other: &'a Set<T, S>) -> Difference<'a, T, S> {
+        Difference {
+            inner: self.iter(),
+            other,
+        }
+    }
+    /// Iterate lazily over the values in `self` or `other`, but not in both.
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Set<T, S>,
+    ) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference {
+            inner: self.difference(other).chain(other.difference(self)),
+        }
+    }
+    /// Check if every value of `self` is also in `other`.
+    pub fn is_subset(&self, other: &Set<T, S>) -> bool {
+        self.len() <= other.len() && self.iter().all(|value| other.contains(value))
+    }
+    /// Check if every value of `other` is also in `self`.
+    pub fn is_superset(&self, other: &Set<T, S>) -> bool {
+        other.is_subset(self)
+    }
+    /// Check if `self` and `other` have no value in common.
+    pub fn is_disjoint(&self, other: &Set<T, S>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+/// Default implementation for `Set`.
+impl<T, S> Default for Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_slots_and_hasher(64, S::default())
+    }
+}
+
+impl<T, S> Debug for Set<T, S>
+where
+    T: Hash + Eq + Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Two sets are equal when they hold the same values.
+impl<T, S> PartialEq for Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> Eq for Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> FromIterator<T> for Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::with_hasher(S::default());
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> Extend<T> for Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| {
+            self.insert(value);
+        });
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for Set<T, S>
+where
+    T: Hash + Eq + Copy,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, S> IntoIterator for Set<T, S> {
+    type Item = T;
+    type IntoIter = SetIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SetIntoIter {
+            remaining: self.elements.items,
+            inner: self.elements.slots.into_iter().flatten(),
+        }
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a Set<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = &'a T;
+    type IntoIter = SetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// `&a | &b` builds the union of two sets.
+impl<T, S> BitOr<&Set<T, S>> for &Set<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Set<T, S>;
+
+    fn bitor(self, other: &Set<T, S>) -> Set<T, S> {
+        self.union(other).cloned().collect()
+    }
+}
+
+/// `&a & &b` builds the intersection of two sets.
+impl<T, S> BitAnd<&Set<T, S>> for &Set<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Set<T, S>;
+
+    fn bitand(self, other: &Set<T, S>) -> Set<T, S> {
+        self.intersection(other).cloned().collect()
+    }
+}
+
+/// `&a - &b` builds the difference of two sets.
+impl<T, S> Sub<&Set<T, S>> for &Set<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Set<T, S>;
+
+    fn sub(self, other: &Set<T, S>) -> Set<T, S> {
+        self.difference(other).cloned().collect()
+    }
+}
+
+/// `&a ^ &b` builds the symmetric difference of two sets.
+impl<T, S> BitXor<&Set<T, S>> for &Set<T, S>
+where
+    T: Hash + Eq + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = Set<T, S>;
+
+    fn bitxor(self, other: &Set<T, S>) -> Set<T, S> {
+        self.symmetric_difference(other).cloned().collect()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    fn sorted<'a>(values: impl Iterator<Item = &'a i32>) -> Vec<i32> {
+        let mut values: Vec<_> = values.copied().collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let mut set = Set::new(4);
+        assert!(set.insert("read"));
+        assert!(set.insert("write"));
+        assert!(!set.insert("read"));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("read"));
+        assert!(!set.contains("exec"));
+        assert_eq!(set.get("write").unwrap(), &"write");
+        assert_eq!(set.remove("read").unwrap(), "read");
+        assert!(set.remove("read").is_err());
+        assert!(!set.contains("read"));
+        set.extend(["a", "b", "c"]);
+        set.retain(|value| *value != "b");
+        assert_eq!(set.iter().count(), 3);
+        assert_eq!(format!("{:?}", Set::<i32>::from_iter([7])), "{7}");
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a: Set<i32> = (1..=5).collect();
+        let b: Set<i32> = (4..=8).collect();
+        let c: Set<i32> = (10..12).collect();
+
+        assert_eq!(sorted(a.union(&b)), (1..=8).collect::<Vec<_>>());
+        assert_eq!(sorted(a.intersection(&b)), vec![4, 5]);
+        assert_eq!(sorted(a.difference(&b)), vec![1, 2, 3]);
+        assert_eq!(sorted(b.difference(&a)), vec![6, 7, 8]);
+        assert_eq!(sorted(a.symmetric_difference(&b)), vec![1, 2, 3, 6, 7, 8]);
+
+        let small: Set<i32> = [2, 3].into_iter().collect();
+        assert!(small.is_subset(&a));
+        assert!(a.is_superset(&small));
+        assert!(!a.is_subset(&b));
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&b));
+
+        assert_eq!(&a | &b, (1..=8).collect());
+        assert_eq!(&a & &b, [4, 5].into_iter().collect());
+        assert_eq!(&a - &b, [1, 2, 3].into_iter().collect());
+        assert_eq!(&a ^ &b, [1, 2, 3, 6, 7, 8].into_iter().collect());
+        assert_ne!(a, b);
+
+        let mut owned: Vec<_> = (&a & &b).into_iter().collect();
+        owned.sort();
+        assert_eq!(owned, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_set_growth() {
+        let mut set = Set::new(0);
+        for i in 0..1000 {
+            assert!(set.insert(i));
+        }
+        assert!(set.capacity() >= 1000);
+        set.retain(|value| value % 2 == 0);
+        assert_eq!(set.len(), 500);
+        // Churn leaves tombstones, which inserts clear out by rebuilding.
+        for round in 0..10 {
+            for i in 0..100 {
+                set.insert(10_000 + round * 100 + i);
+            }
+            for i in 0..100 {
+                set.remove(&(10_000 + round * 100 + i)).unwrap();
+            }
+        }
+        assert_eq!(set.len(), 500);
+        assert_eq!(set.iter().len(), 500);
+        assert!((0..1000).all(|i| set.contains(&i) == (i % 2 == 0)));
+        set.shrink_to_fit();
+        assert!(set.capacity() >= 500);
+        set.reserve(1000);
+        assert!(set.capacity() >= 1500);
+        assert_eq!(set.into_iter().len(), 500);
+    }
+
+    #[test]
+    fn test_set_reseed() {
+        /// Hasher that uses integer values as their own hash.
+        #[derive(Default)]
+        struct IdentityHasher(u64);
+
+        impl Hasher for IdentityHasher {
+            fn finish(&self) -> u64 {
+                self.0
+            }
+            fn write(&mut self, _bytes: &[u8]) {}
+            fn write_u64(&mut self, n: u64) {
+                self.0 = n;
+            }
+        }
+
+        // Values sharing their low bits all probe from the same group.
+        let mut set: Set<u64, BuildHasherDefault<IdentityHasher>> = Set::default();
+        for i in 0..1000 {
+            set.insert(i << 32);
+        }
+        assert_eq!(set.reseeds(), 1);
+        assert_eq!(set.len(), 1000);
+        assert!((0..1000).all(|i| set.contains(&(i << 32))));
+        assert!(!set.contains(&1));
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,302 @@
+use super::{Entry, Result, Table};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::slice;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+
+/// Shards created per available thread by `ShardedTable::default`.
+const SHARDS_PER_THREAD: usize = 4;
+
+/// `ShardedTable` is a concurrent hash table split into independently locked shards.
+///
+/// Every key belongs to the shard picked by its hash, and each shard is a
+/// `Table` behind its own `RwLock`. Writers only block the shard they touch,
+/// and readers of the same shard proceed in parallel, so the table can be
+/// shared between threads through a plain `&` or an `Arc`.
+///
+/// A panic in a closure passed to `get`, `update`, `entry` or `retain`
+/// poisons the shard's lock, which the table ignores: the shard's `Table`
+/// stays consistent and usable. A value that `update` or `entry` was
+/// modifying keeps whatever changes were made before the panic.
+pub struct ShardedTable<K, V, S = RandomState> {
+    shards: Box<[RwLock<Table<K, V, S>>]>,
+    hash_builder: S,
+}
+
+impl<K, V> ShardedTable<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `ShardedTable` with the given number of shards.
+    /// A table always has at least one shard.
+    pub fn new(shards: usize) -> Self {
+        Self::with_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K, V, S> ShardedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Clone,
+{
+    /// Create a new `ShardedTable` with the given number of shards,
+    /// hashing keys with `hash_builder`.
+    pub fn with_hasher(shards: usize, hash_builder: S) -> Self {
+        let shards = (0..shards.max(1))
+            .map(|_| RwLock::new(Table::with_hasher(hash_builder.clone())))
+            .collect();
+        Self {
+            shards,
+            hash_builder,
+        }
+    }
+    /// Get the index of the shard owning the key.
+    fn shard_index<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        // Shards use the high half of the hash, so the slots inside a
+        // shard, picked from the low bits, stay evenly used.
+        ((self.hash_builder.hash_one(key) >> 32) as usize) % self.shards.len()
+    }
+    /// Lock the shard owning the key for reading.
+    fn read<Q>(&self, key: &Q) -> RwLockReadGuard<'_, Table<K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.shards[self.shard_index(key)]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Lock the shard owning the key for writing.
+    fn write<Q>(&self, key: &Q) -> RwLockWriteGuard<'_, Table<K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.shards[self.shard_index(key)]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Insert a new key-value pair into the table.
+    /// If the key is already present, its value is replaced and the old one returned.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.write(&key).insert(key, value)
+    }
+    /// Call `f` with the value for the given key while its shard is read-locked.
+    pub fn get<Q, F, R>(&self, key: &Q, f: F) -> Result<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&V) -> R,
+    {
+        self.read(key).get(key).map(f)
+    }
+    /// Get a clone of the value for the given key.
+    pub fn get_cloned<Q>(&self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.get(key, V::clone)
+    }
+    /// Check if the table contains the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read(key).contains_key(key)
+    }
+    /// Remove the key-value pair from the table.
+    pub fn remove<Q>(&self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.write(key).remove(key)
+    }
+    /// Call `f` with the value for the given key while its shard is write-locked.
+    pub fn update<Q, F, R>(&self, key: &Q, f: F) -> Result<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V) -> R,
+    {
+        self.write(key).update(key).map(f)
+    }
+    /// Call `f` with the entry for the given key while its shard is write-locked.
+    pub fn entry<F, R>(&self, key: K, f: F) -> R
+    where
+        F: FnOnce(Entry<'_, K, V, S>) -> R,
+    {
+        f(self.write(&key).entry(key))
+    }
+    /// Get the number of elements in the table.
+    /// Shards are counted one after the other, so concurrent writes may
+    /// not be reflected.
+    pub fn len(&self) -> usize {
+        self.shards().map(|shard| shard.len()).sum()
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shards().all(|shard| shard.is_empty())
+    }
+    /// Get the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+    /// Iterate over the shards, read-locking each one only while it is yielded.
+    pub fn shards(&self) -> Shards<'_, K, V, S> {
+        Shards {
+            inner: self.shards.iter(),
+        }
+    }
+    /// Call `f` with every key-value pair, one read-locked shard at a time.
+    pub fn for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V),
+    {
+        for shard in self.shards() {
+            shard.iter().for_each(|(key, value)| f(key, value));
+        }
+    }
+    /// Keep only the key-value pairs for which `f` returns true,
+    /// write-locking one shard at a time.
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for shard in self.shards.iter() {
+            shard
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(&mut f);
+        }
+    }
+}
+/// Default implementation for `ShardedTable`, with a few shards per available thread.
+impl<K, V, S> Default for ShardedTable<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Clone + Default,
+{
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_hasher(threads * SHARDS_PER_THREAD, S::default())
+    }
+}
+
+/// Iterator over the read-locked shards of a `ShardedTable`,
+/// created by `ShardedTable::shards`.
+pub struct Shards<'a, K, V, S> {
+    inner: slice::Iter<'a, RwLock<Table<K, V, S>>>,
+}
+
+impl<'a, K, V, S> Iterator for Shards<'a, K, V, S> {
+    type Item = RwLockReadGuard<'a, Table<K, V, S>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|shard| shard.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Shards<'_, K, V, S> {}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sharded_table_ops() {
+        let table = ShardedTable::new(4);
+        assert_eq!(table.insert("key1", 1), None);
+        assert_eq!(table.insert("key1", 2), Some(1));
+        assert_eq!(table.get(&"key1", |v| *v * 10).unwrap(), 20);
+        assert_eq!(table.get_cloned(&"key1").unwrap(), 2);
+        table.update(&"key1", |v| *v += 1).unwrap();
+        assert_eq!(table.get_cloned(&"key1").unwrap(), 3);
+        table.entry("key2", |entry| *entry.or_insert(0) += 5);
+        assert_eq!(table.get_cloned(&"key2").unwrap(), 5);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.remove(&"key1").unwrap(), 3);
+        assert!(!table.contains_key(&"key1"));
+        assert!(table.get(&"key1", |_| ()).is_err());
+        assert!(table.update(&"key1", |_| ()).is_err());
+        assert_eq!(ShardedTable::<i32, i32>::new(0).shard_count(), 1);
+    }
+
+    #[test]
+    fn test_sharded_table_shards() {
+        let table = ShardedTable::new(8);
+        for i in 0..1000 {
+            table.insert(i, i);
+        }
+        assert_eq!(table.shards().len(), 8);
+        assert_eq!(table.shards().map(|shard| shard.len()).sum::<usize>(), 1000);
+        assert!(table.shards().all(|shard| !shard.is_empty()));
+        let mut sum = 0;
+        table.for_each(|_, v| sum += v);
+        assert_eq!(sum, (0..1000).sum::<i32>());
+        table.retain(|k, _| k % 2 == 0);
+        assert_eq!(table.len(), 500);
+    }
+
+    #[test]
+    fn test_sharded_table_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ShardedTable<String, Vec<u8>>>();
+
+        let table = Arc::new(ShardedTable::<usize, usize>::default());
+        thread::scope(|scope| {
+            for t in 0..8 {
+                let table = Arc::clone(&table);
+                scope.spawn(move || {
+                    for i in 0..500 {
+                        table.insert(t * 500 + i, i);
+                        table.entry(i, |entry| *entry.or_insert(0) += 0);
+                        assert_eq!(table.get_cloned(&(t * 500 + i)).unwrap(), i);
+                    }
+                });
+            }
+        });
+        assert_eq!(table.len(), 4000);
+        assert_eq!(table.get_cloned(&3999).unwrap(), 499);
+    }
+
+    #[test]
+    fn test_sharded_table_poisoned() {
+        let table = ShardedTable::new(1);
+        for i in 0..100 {
+            table.insert(i, i);
+        }
+        let result = std::panic::catch_unwind(|| {
+            table.retain(|k, _| {
+                assert!(*k != 50, "predicate panicked");
+                k % 2 == 0
+            })
+        });
+        assert!(result.is_err());
+        assert!(table.shards[0].is_poisoned());
+        // The shard still counts exactly the pairs it holds.
+        let mut count = 0;
+        table.for_each(|_, _| count += 1);
+        assert_eq!(table.len(), count);
+        table.update(&50, |v| *v += 1).unwrap();
+        assert_eq!(table.get_cloned(&50).unwrap(), 51);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,218 @@
+use super::{Error, Result};
+
+/// `Codec` converts keys and values to and from the bytes of a snapshot.
+///
+/// Encodings must be self-delimiting and at least one byte long: `decode`
+/// reads exactly the bytes that `encode` wrote and leaves the rest of the
+/// input for the next field.
+/// Integers are little-endian, and lengths are written as `u64`.
+pub trait Codec: Sized {
+    /// Append the bytes of `self` to `buf`.
+    fn encode<W: ByteSink>(&self, buf: &mut W);
+    /// Read a value from the front of `input`, advancing past its bytes.
+    /// Fails with `CorruptSnapshot` if the bytes are missing or invalid.
+    fn decode(input: &mut &[u8]) -> Result<Self>;
+}
+
+/// Largest number of items preallocated from a decoded length. The length is
+/// only checked against the bytes left, and an item can take far more memory
+/// than its one byte, so a corrupt length can't claim a huge allocation.
+pub(crate) const MAX_PREALLOCATED: usize = 4096;
+
+/// `ByteSink` receives the bytes written by `Codec::encode`.
+///
+/// `Vec<u8>` collects them, while other sinks can consume them as they come,
+/// such as a hasher fed a key without encoding it to a buffer first.
+pub trait ByteSink {
+    /// Append `bytes` to the sink.
+    fn put(&mut self, bytes: &[u8]);
+}
+
+impl ByteSink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// Split the first `len` bytes off `input`.
+pub(super) fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(Error::CorruptSnapshot);
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+/// Read a length written as `u64`, rejecting any longer than the input left.
+/// Every item takes at least one byte, so a longer one is corrupt.
+fn decode_len(input: &mut &[u8]) -> Result<usize> {
+    let len = u64::decode(input)?;
+    match usize::try_from(len) {
+        Ok(len) if len <= input.len() => Ok(len),
+        _ => Err(Error::CorruptSnapshot),
+    }
+}
+
+macro_rules! impl_codec_for_numbers {
+    ($($ty:ty),*) => {
+        $(
+            impl Codec for $ty {
+                fn encode<W: ByteSink>(&self, buf: &mut W) {
+                    buf.put(&self.to_le_bytes());
+                }
+                fn decode(input: &mut &[u8]) -> Result<Self> {
+                    let bytes = take(input, std::mem::size_of::<$ty>())?;
+                    Ok(<$ty>::from_le_bytes(bytes.try_into().expect("sized slice")))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Codec for usize {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        (*self as u64).encode(buf);
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        usize::try_from(u64::decode(input)?).map_err(|_| Error::CorruptSnapshot)
+    }
+}
+
+impl Codec for isize {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        (*self as i64).encode(buf);
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        isize::try_from(i64::decode(input)?).map_err(|_| Error::CorruptSnapshot)
+    }
+}
+
+impl Codec for bool {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        buf.put(&[*self as u8]);
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::CorruptSnapshot),
+        }
+    }
+}
+
+impl Codec for char {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        (*self as u32).encode(buf);
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        char::from_u32(u32::decode(input)?).ok_or(Error::CorruptSnapshot)
+    }
+}
+
+impl Codec for String {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        self.len().encode(buf);
+        buf.put(self.as_bytes());
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        let len = decode_len(input)?;
+        let bytes = take(input, len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::CorruptSnapshot)
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        self.len().encode(buf);
+        self.iter().for_each(|item| item.encode(buf));
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        let len = decode_len(input)?;
+        let mut items = Vec::with_capacity(len.min(MAX_PREALLOCATED));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        match self {
+            None => buf.put(&[0]),
+            Some(value) => {
+                buf.put(&[1]);
+                value.encode(buf);
+            }
+        }
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        match u8::decode(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(input)?)),
+            _ => Err(Error::CorruptSnapshot),
+        }
+    }
+}
+
+impl<A: Codec, B: Codec> Codec for (A, B) {
+    fn encode<W: ByteSink>(&self, buf: &mut W) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+    }
+    fn decode(input: &mut &[u8]) -> Result<Self> {
+        Ok((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Codec + PartialEq + std::fmt::Debug>(value: T) {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        let mut input = buf.as_slice();
+        assert_eq!(T::decode(&mut input).unwrap(), value);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        round_trip(0xABu8);
+        round_trip(-12345i32);
+        round_trip(u128::MAX);
+        round_trip(usize::MAX);
+        round_trip(-1.5f64);
+        round_trip(true);
+        round_trip('ñ');
+        round_trip("héllo".to_string());
+        round_trip(vec![Some(1u16), None, Some(3)]);
+        round_trip((String::new(), vec![false; 3]));
+
+        assert!(matches!(
+            bool::decode(&mut &[2u8][..]),
+            Err(Error::CorruptSnapshot)
+        ));
+        assert!(char::decode(&mut &0xD800u32.to_le_bytes()[..]).is_err());
+        assert!(u32::decode(&mut &[1u8, 2][..]).is_err());
+        let mut buf = Vec::new();
+        u64::MAX.encode(&mut buf);
+        assert!(String::decode(&mut buf.as_slice()).is_err());
+        assert!(Vec::<u8>::decode(&mut buf.as_slice()).is_err());
+
+        // A length matching the bytes left decodes past the preallocation.
+        let items = vec![1u8; MAX_PREALLOCATED * 2];
+        round_trip(items.clone());
+        // But a length claiming one large item per byte fails without
+        // reserving room for all of them.
+        let mut buf = Vec::new();
+        items.encode(&mut buf);
+        assert!(Vec::<(u128, u128)>::decode(&mut buf.as_slice()).is_err());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,210 @@
+//! Binary snapshots of a `Table`.
+//!
+//! A snapshot is laid out as:
+//!
+//! | Field    | Size     | Contents                                  |
+//! |----------|----------|-------------------------------------------|
+//! | magic    | 4 bytes  | `RDST`                                    |
+//! | version  | 2 bytes  | format version, little-endian             |
+//! | count    | 8 bytes  | number of pairs, little-endian            |
+//! | pairs    | variable | each key then its value, as `Codec` bytes |
+//! | checksum | 4 bytes  | CRC-32 of everything before it            |
+
+mod codec;
+
+pub(super) use codec::MAX_PREALLOCATED;
+pub use codec::{ByteSink, Codec};
+
+use super::{Error, Result, Table};
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+
+/// Bytes every snapshot starts with.
+const MAGIC: [u8; 4] = *b"RDST";
+/// Version of the snapshot format written by `write_snapshot`.
+const VERSION: u16 = 1;
+/// Size of the magic and version fields every file starts with.
+const HEADER_LEN: usize = 4 + 2;
+/// Size of the checksum every file ends with.
+const CHECKSUM_LEN: usize = 4;
+
+/// CRC-32 (IEEE) lookup table, built at compile time.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Compute the CRC-32 (IEEE) checksum of `bytes`.
+pub(super) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Map an I/O error, reporting input that ended early as a corrupt snapshot.
+pub(super) fn io_error(err: io::Error) -> Error {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => Error::CorruptSnapshot,
+        _ => Error::Io(err),
+    }
+}
+
+/// Start the bytes of a file with its magic and format version.
+pub(super) fn file_header(magic: [u8; 4], version: u16) -> Vec<u8> {
+    let mut buf = magic.to_vec();
+    buf.extend_from_slice(&version.to_le_bytes());
+    buf
+}
+
+/// Append the checksum to the bytes of a file and write them to `writer`.
+pub(super) fn write_file(mut buf: Vec<u8>, writer: &mut impl Write) -> Result<()> {
+    buf.extend_from_slice(&crc32(&buf).to_le_bytes());
+    writer.write_all(&buf).map_err(io_error)?;
+    writer.flush().map_err(io_error)
+}
+
+/// Read a whole file, check its checksum, magic and version, and return the
+/// bytes in between.
+pub(super) fn read_file(mut reader: impl Read, magic: [u8; 4], version: u16) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).map_err(io_error)?;
+    if buf.len() < HEADER_LEN + CHECKSUM_LEN {
+        return Err(Error::CorruptSnapshot);
+    }
+    let checksum = buf.split_off(buf.len() - CHECKSUM_LEN);
+    if crc32(&buf).to_le_bytes()[..] != checksum[..]
+        || buf[..HEADER_LEN] != file_header(magic, version)
+    {
+        return Err(Error::CorruptSnapshot);
+    }
+    Ok(buf.split_off(HEADER_LEN))
+}
+
+impl<K, V, S> Table<K, V, S>
+where
+    K: Hash + Eq + Codec,
+    V: Codec,
+    S: BuildHasher,
+{
+    /// Write every pair of the table to `writer` as a snapshot.
+    pub fn write_snapshot(&self, writer: &mut impl Write) -> Result<()> {
+        let mut buf = file_header(MAGIC, VERSION);
+        self.len().encode(&mut buf);
+        for (key, value) in self.iter() {
+            key.encode(&mut buf);
+            value.encode(&mut buf);
+        }
+        write_file(buf, writer)
+    }
+    /// Read a table back from a snapshot written by `write_snapshot`.
+    /// Fails with `CorruptSnapshot` on truncated, altered or unknown input.
+    pub fn read_snapshot(reader: impl Read) -> Result<Self>
+    where
+        S: Default,
+    {
+        Self::read_snapshot_with_hasher(reader, S::default())
+    }
+    /// Read a table back from a snapshot, hashing keys with `hash_builder`.
+    pub fn read_snapshot_with_hasher(reader: impl Read, hash_builder: S) -> Result<Self> {
+        let buf = read_file(reader, MAGIC, VERSION)?;
+        let mut input = buf.as_slice();
+        let count = u64::decode(&mut input)?;
+        // Every pair takes at least one byte, so a larger count is corrupt.
+        // A smaller one still only sizes the table up to a cap, as a pair
+        // can take far more memory than its encoded bytes.
+        if count > input.len() as u64 {
+            return Err(Error::CorruptSnapshot);
+        }
+        let capacity = (count as usize).min(MAX_PREALLOCATED);
+        let mut table = Self::with_capacity_and_hasher(capacity, hash_builder);
+        for _ in 0..count {
+            let key = K::decode(&mut input)?;
+            let value = V::decode(&mut input)?;
+            if table.insert(key, value).is_some() {
+                return Err(Error::CorruptSnapshot);
+            }
+        }
+        match input.is_empty() {
+            true => Ok(table),
+            false => Err(Error::CorruptSnapshot),
+        }
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(table: &Table<String, Vec<u32>>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        table.write_snapshot(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let mut table: Table<String, Vec<u32>> = Table::default();
+        table.insert("empty".to_string(), Vec::new());
+        for i in 0..100 {
+            table.insert(format!("key{}", i), (0..i % 7).collect());
+        }
+        let buf = snapshot(&table);
+        assert_eq!(&buf[..4], b"RDST");
+        let restored: Table<String, Vec<u32>> = Table::read_snapshot(buf.as_slice()).unwrap();
+        assert_eq!(restored, table);
+
+        let empty: Table<u8, bool> = Table::default();
+        let mut buf = Vec::new();
+        empty.write_snapshot(&mut buf).unwrap();
+        assert!(Table::<u8, bool>::read_snapshot(buf.as_slice())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_corruption() {
+        let table: Table<String, Vec<u32>> = (0..10).map(|i| (i.to_string(), vec![i; 3])).collect();
+        let buf = snapshot(&table);
+        let read = |bytes: &[u8]| Table::<String, Vec<u32>>::read_snapshot(bytes);
+
+        // Every truncation and every flipped byte is caught, never a panic.
+        for len in 0..buf.len() {
+            assert!(matches!(read(&buf[..len]), Err(Error::CorruptSnapshot)));
+        }
+        for i in 0..buf.len() {
+            let mut bytes = buf.clone();
+            bytes[i] ^= 0x40;
+            assert!(matches!(read(&bytes), Err(Error::CorruptSnapshot)));
+        }
+
+        // A well-formed checksum does not make an unknown version readable.
+        let mut bytes = buf[..buf.len() - CHECKSUM_LEN].to_vec();
+        bytes[4] = 2;
+        bytes.extend_from_slice(&crc32(&bytes).to_le_bytes());
+        assert!(matches!(read(&bytes), Err(Error::CorruptSnapshot)));
+        // Nor a count larger than the pairs that follow.
+        let mut bytes = buf[..buf.len() - CHECKSUM_LEN].to_vec();
+        bytes[6] = 11;
+        bytes.extend_from_slice(&crc32(&bytes).to_le_bytes());
+        assert!(matches!(read(&bytes), Err(Error::CorruptSnapshot)));
+    }
+}
+// endregion: --- Tests
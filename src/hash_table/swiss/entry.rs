@@ -0,0 +1,186 @@
+//! Entry API for `Table`.
+//!
+//! `Entry` is a view into a single slot of the table, either occupied or vacant,
+//! obtained with a single lookup through `Table::entry`.
+use super::{Location, Table};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+/// A view into a single entry of a `Table`, which may be occupied or vacant.
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// A view into an entry whose key is already present in the `Table`.
+pub struct OccupiedEntry<'a, K, V, S> {
+    pub(super) table: &'a mut Table<K, V, S>,
+    pub(super) location: Location,
+}
+
+/// A view into an entry whose key is not yet present in the `Table`.
+pub struct VacantEntry<'a, K, V, S> {
+    pub(super) table: &'a mut Table<K, V, S>,
+    pub(super) hash: u64,
+    pub(super) key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Get the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+    /// Insert `default` if the entry is vacant and return the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+    /// Insert the result of `default` if the entry is vacant and return the value.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+    /// Insert the default value if the entry is vacant and return the value.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+    /// Modify the value in place if the entry is occupied.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Get the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.table.pair(self.location).0
+    }
+    /// Get the value of the entry.
+    pub fn get(&self) -> &V {
+        &self.table.pair(self.location).1
+    }
+    /// Get a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.table.pair_mut(self.location).1
+    }
+    /// Convert the entry into a mutable reference to its value,
+    /// bound to the lifetime of the table.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.table.pair_mut(self.location).1
+    }
+    /// Replace the value of the entry, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+    /// Remove the entry from the table, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+    /// Remove the entry from the table, returning its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.table.take(self.location)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Get the key that would be used when inserting through the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+    /// Take ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+    /// Insert the value under the entry's key, returning a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let location = self.table.insert_new(self.hash, self.key, value);
+        &mut self.table.pair_mut(location).1
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_ops() {
+        let mut table: Table<&str, i32> = Table::new(0);
+        *table.entry("a").or_insert(1) += 10;
+        assert_eq!(table.get(&"a").unwrap(), &11);
+        *table.entry("a").or_insert(1) += 10;
+        assert_eq!(table.get(&"a").unwrap(), &21);
+        assert_eq!(*table.entry("b").or_insert_with(|| 2), 2);
+        assert_eq!(*table.entry("c").or_default(), 0);
+        table.entry("c").and_modify(|v| *v = 3).or_insert(99);
+        table.entry("d").and_modify(|v| *v = 3).or_insert(4);
+        assert_eq!(table.get(&"c").unwrap(), &3);
+        assert_eq!(table.get(&"d").unwrap(), &4);
+        assert_eq!(table.entry("d").key(), &"d");
+        assert_eq!(table.len(), 4);
+
+        match table.entry("a") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.insert(5), 21);
+                assert_eq!(entry.get(), &5);
+                assert_eq!(entry.remove_entry(), ("a", 5));
+            }
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert!(table.get(&"a").is_err());
+        assert_eq!(table.len(), 3);
+
+        match table.entry("e") {
+            Entry::Occupied(_) => panic!("expected a vacant entry"),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &"e");
+                *entry.insert(6) += 1;
+            }
+        }
+        assert_eq!(table.get(&"e").unwrap(), &7);
+    }
+
+    #[test]
+    fn test_entry_growth() {
+        let mut table = Table::new(1);
+        for i in 0..100 {
+            *table.entry(i % 10).or_insert(0) += 1;
+            *table.entry(i).or_default() += 1;
+        }
+        assert_eq!(table.len(), 100);
+        assert_eq!(table.get(&3).unwrap(), &11);
+        assert_eq!(table.get(&42).unwrap(), &1);
+    }
+}
+// endregion: --- Tests
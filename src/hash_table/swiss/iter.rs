@@ -0,0 +1,334 @@
+//! Iterators over the entries of a `Table`.
+use super::{AllSlots, PairSlot, Table};
+use std::iter::Flatten;
+use std::{slice, vec};
+
+/// Iterator over the key-value pairs of a `Table`, created by `Table::iter`.
+pub struct Iter<'a, K, V> {
+    pub(super) inner: Flatten<AllSlots<slice::Iter<'a, PairSlot<K, V>>>>,
+    pub(super) remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Iterator over the key-value pairs of a `Table` with mutable values,
+/// created by `Table::iter_mut`.
+pub struct IterMut<'a, K, V> {
+    pub(super) inner: Flatten<AllSlots<slice::IterMut<'a, PairSlot<K, V>>>>,
+    pub(super) remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.inner.next()?;
+        self.remaining -= 1;
+        Some((&*key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Owning iterator over the key-value pairs of a `Table`.
+pub struct IntoIter<K, V> {
+    pub(super) inner: Flatten<AllSlots<vec::IntoIter<PairSlot<K, V>>>>,
+    pub(super) remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pair = self.inner.next()?;
+        self.remaining -= 1;
+        Some(pair)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+/// Iterator over the keys of a `Table`, created by `Table::keys`.
+pub struct Keys<'a, K, V> {
+    pub(super) inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// Iterator over the values of a `Table`, created by `Table::values`.
+pub struct Values<'a, K, V> {
+    pub(super) inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// Iterator over mutable references to the values of a `Table`,
+/// created by `Table::values_mut`.
+pub struct ValuesMut<'a, K, V> {
+    pub(super) inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+/// Draining iterator over the key-value pairs of a `Table`, created by `Table::drain`.
+///
+/// The table keeps its slots and is left empty, even if the iterator
+/// is dropped before it is fully consumed.
+pub struct Drain<'a, K, V, S> {
+    pub(super) old: Flatten<vec::IntoIter<PairSlot<K, V>>>,
+    pub(super) table: &'a mut Table<K, V, S>,
+    pub(super) index: usize,
+}
+
+impl<K, V, S> Iterator for Drain<'_, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(pair) = self.old.next() {
+            self.table.len -= 1;
+            return Some(pair);
+        }
+        let elements = &mut self.table.elements;
+        while self.index < elements.len() {
+            let index = self.index;
+            self.index += 1;
+            if elements.slots[index].is_some() {
+                self.table.len -= 1;
+                return Some(elements.take(index));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.table.len, Some(self.table.len))
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Drain<'_, K, V, S> {}
+
+impl<K, V, S> Drop for Drain<'_, K, V, S> {
+    fn drop(&mut self) {
+        self.table.elements.clear();
+        self.table.len = 0;
+    }
+}
+
+/// Iterator removing and yielding the entries that match a predicate,
+/// created by `Table::extract_if`.
+///
+/// Entries that are not reached before the iterator is dropped stay in the table.
+pub struct ExtractIf<'a, K, V, S, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    /// Whether the walk is still in the slots being migrated.
+    pub(super) old: bool,
+    pub(super) table: &'a mut Table<K, V, S>,
+    pub(super) index: usize,
+    pub(super) predicate: F,
+}
+
+impl<K, V, S, F> Iterator for ExtractIf<'_, K, V, S, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let raw = self.table.raw_mut(self.old);
+            while self.index < raw.len() {
+                let index = self.index;
+                self.index += 1;
+                if let Some((key, value)) = raw.slots[index].as_mut() {
+                    if (self.predicate)(key, value) {
+                        let pair = raw.take(index);
+                        self.table.len -= 1;
+                        return Some(pair);
+                    }
+                }
+            }
+            if !self.old {
+                return None;
+            }
+            self.old = false;
+            self.index = 0;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.table.len))
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table<i32, i32> {
+        let mut table = Table::new(4);
+        for i in 0..20 {
+            table.insert(i, i * 10);
+        }
+        table
+    }
+
+    #[test]
+    fn test_table_iter() {
+        let mut table = sample();
+        let mut pairs: Vec<_> = table.iter().map(|(k, v)| (*k, *v)).collect();
+        pairs.sort();
+        assert_eq!(pairs, (0..20).map(|i| (i, i * 10)).collect::<Vec<_>>());
+        assert_eq!(table.iter().len(), 20);
+
+        let mut keys: Vec<_> = table.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, (0..20).collect::<Vec<_>>());
+        assert_eq!(table.values().sum::<i32>(), 1900);
+
+        for (key, value) in table.iter_mut() {
+            *value += key;
+        }
+        for value in table.values_mut() {
+            *value += 1;
+        }
+        assert_eq!(table.get(&3).unwrap(), &34);
+        assert_eq!((&table).into_iter().count(), 20);
+        for (_, value) in &mut table {
+            *value = 0;
+        }
+        assert_eq!(table.values().sum::<i32>(), 0);
+
+        let mut owned: Vec<_> = table.into_iter().map(|(k, _)| k).collect();
+        owned.sort();
+        assert_eq!(owned, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_table_drain() {
+        let mut table = sample();
+        let mut drained: Vec<_> = table.drain().collect();
+        drained.sort();
+        assert_eq!(drained.len(), 20);
+        assert!(table.is_empty());
+        assert!(table.get(&1).is_err());
+
+        let mut table = sample();
+        let capacity = table.capacity();
+        assert_eq!(table.drain().take(3).count(), 3);
+        assert!(table.is_empty());
+        assert_eq!(table.iter().count(), 0);
+        assert_eq!(table.capacity(), capacity);
+        table.insert(1, 1);
+        assert_eq!(table.get(&1).unwrap(), &1);
+    }
+
+    #[test]
+    fn test_table_retain_and_extract_if() {
+        let mut table = sample();
+        table.retain(|key, value| {
+            *value += 1;
+            key % 2 == 0
+        });
+        assert_eq!(table.len(), 10);
+        assert_eq!(table.get(&4).unwrap(), &41);
+        assert!(table.get(&5).is_err());
+
+        let mut extracted: Vec<_> = table.extract_if(|key, _| key % 4 == 0).collect();
+        extracted.sort();
+        assert_eq!(
+            extracted,
+            vec![(0, 1), (4, 41), (8, 81), (12, 121), (16, 161)]
+        );
+        assert_eq!(table.len(), 5);
+        let mut keys: Vec<_> = table.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, vec![2, 6, 10, 14, 18]);
+
+        assert_eq!(table.extract_if(|_, _| true).take(2).count(), 2);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.iter().count(), 3);
+    }
+
+    #[test]
+    fn test_table_retain_panic() {
+        let mut table = Table::new(4);
+        for i in 0..100 {
+            table.insert(i, i);
+        }
+        let mut seen = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            table.retain(|_, _| {
+                seen += 1;
+                assert!(seen <= 50, "predicate panicked");
+                false
+            })
+        }));
+        assert!(result.is_err());
+        // The pairs rejected before the panic are gone, and `len` agrees.
+        assert_eq!(table.len(), 50);
+        assert_eq!(table.iter().count(), 50);
+        assert_eq!(table.iter().len(), 50);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,1151 @@
+mod entry;
+mod iter;
+mod raw;
+#[cfg(feature = "serde")]
+mod serde;
+mod stats;
+
+use super::{Error, Result};
+use raw::Slot;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::Chain;
+use std::ops::Index;
+use std::{mem, slice};
+
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use iter::{Drain, ExtractIf, IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
+pub(super) use raw::{slot_count, RawTable, GROUP_WIDTH};
+pub use stats::TableStats;
+
+/// Slot of a `Table`, holding a key-value pair.
+type PairSlot<K, V> = Slot<(K, V)>;
+/// Slots still being migrated, followed by the current ones.
+type AllSlots<I> = Chain<I, I>;
+
+/// Load factor a new `Table` grows at, unless configured otherwise.
+pub(super) const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.75;
+/// Fewest slots migrated by every mutating operation while a rehash is in progress.
+const REHASH_STEP: usize = 4;
+/// Groups an insert may probe before the table suspects colliding keys and
+/// rebuilds itself with a fresh seed.
+pub(super) const MAX_PROBE_GROUPS: usize = 16;
+
+/// `Table` is a simple hash table implementation.
+///
+/// Pairs are stored inline with open addressing, Swiss-table style: a
+/// separate array holds one control byte per slot with 7 bits of the key's
+/// hash, and lookups probe it a group of 8 bytes at a time, so most slots
+/// holding other keys are skipped without comparing keys.
+///
+/// The table grows on its own once the ratio between entries and slots
+/// would cross the maximum load factor. Resizing is incremental: the old and
+/// new slot arrays live side by side, every later insert, update or removal
+/// migrates a few slots, and lookups consult both arrays until the migration
+/// is done.
+///
+/// Keys are hashed with the `BuildHasher` given as `S`, which defaults to the
+/// randomly keyed SipHash of the standard library. If an insert still has to
+/// probe more than `MAX_PROBE_GROUPS` groups, as happens when a weak hasher
+/// meets keys crafted to collide, the table picks a fresh random seed, mixes
+/// it into every hash and rebuilds itself.
+#[derive(Clone)]
+pub struct Table<K, V, S = RandomState> {
+    elements: RawTable<(K, V)>,
+    rehash: Option<Rehash<K, V>>,
+    len: usize,
+    max_load_factor: f64,
+    /// Number of times the slot array was replaced.
+    resizes: usize,
+    /// Seed mixed into every hash, or 0 until the table is first reseeded.
+    seed: u64,
+    /// Number of times the table was rebuilt with a fresh seed.
+    reseeds: usize,
+    /// Length of the table when it was last reseeded.
+    reseed_len: usize,
+    hash_builder: S,
+}
+
+/// Slot array a `Table` is migrating away from.
+#[derive(Clone)]
+struct Rehash<K, V> {
+    elements: RawTable<(K, V)>,
+    /// Index of the next slot to migrate. Every slot before it is free.
+    next: usize,
+}
+
+/// Hash a key with the table's `BuildHasher`, mixing in its seed if it has one.
+pub(super) fn seeded_hash<S, Q>(hash_builder: &S, seed: u64, key: &Q) -> u64
+where
+    S: BuildHasher,
+    Q: Hash + ?Sized,
+{
+    if seed == 0 {
+        return hash_builder.hash_one(key);
+    }
+    let mut hasher = hash_builder.build_hasher();
+    hasher.write_u64(seed);
+    key.hash(&mut hasher);
+    // Scramble the result too, for hashers that ignore the seed: keys sharing
+    // their low bits still land in different groups.
+    let mut z = hasher.finish() ^ seed;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Draw a fresh, nonzero seed.
+pub(super) fn fresh_seed() -> u64 {
+    RandomState::new().hash_one(0u64) | 1
+}
+
+/// Where a pair is stored: which slot array and which slot.
+#[derive(Clone, Copy)]
+struct Location {
+    old: bool,
+    index: usize,
+}
+
+impl<K, V> Table<K, V, RandomState>
+where
+    K: Hash + Eq,
+{
+    /// Create a new `Table` with the given number of slots,
+    /// rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_slots_and_hasher(slot_count(capacity), RandomState::new())
+    }
+    /// Create a new `Table` able to hold `capacity` elements without growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> Table<K, V, S> {
+    /// Chain the slots still being migrated with the current ones.
+    fn all_slots(&self) -> AllSlots<slice::Iter<'_, PairSlot<K, V>>> {
+        let old = self
+            .rehash
+            .as_ref()
+            .map_or(&[][..], |rehash| &rehash.elements.slots);
+        old.iter().chain(self.elements.slots.iter())
+    }
+    /// Chain the slots still being migrated with the current ones, mutably.
+    fn all_slots_mut(&mut self) -> AllSlots<slice::IterMut<'_, PairSlot<K, V>>> {
+        let old = self
+            .rehash
+            .as_mut()
+            .map_or(&mut [][..], |rehash| &mut rehash.elements.slots);
+        old.iter_mut().chain(self.elements.slots.iter_mut())
+    }
+    /// Get the slot array a location points into.
+    fn raw(&self, old: bool) -> &RawTable<(K, V)> {
+        match (old, self.rehash.as_ref()) {
+            (true, Some(rehash)) => &rehash.elements,
+            (true, None) => unreachable!("location points into a finished rehash"),
+            (false, _) => &self.elements,
+        }
+    }
+    /// Get the slot array a location points into, mutably.
+    fn raw_mut(&mut self, old: bool) -> &mut RawTable<(K, V)> {
+        match (old, self.rehash.as_mut()) {
+            (true, Some(rehash)) => &mut rehash.elements,
+            (true, None) => unreachable!("location points into a finished rehash"),
+            (false, _) => &mut self.elements,
+        }
+    }
+    /// Get the pair stored at the location.
+    fn pair(&self, location: Location) -> &(K, V) {
+        self.raw(location.old).slots[location.index]
+            .as_ref()
+            .expect("location points to a full slot")
+    }
+    /// Get the pair stored at the location, mutably.
+    fn pair_mut(&mut self, location: Location) -> &mut (K, V) {
+        self.raw_mut(location.old).slots[location.index]
+            .as_mut()
+            .expect("location points to a full slot")
+    }
+    /// Remove the pair stored at the location.
+    fn take(&mut self, location: Location) -> (K, V) {
+        self.len -= 1;
+        self.raw_mut(location.old).take(location.index)
+    }
+}
+
+impl<K, V, S> Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a new, empty `Table` that hashes keys with `hash_builder`.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_slots_and_hasher(0, hash_builder)
+    }
+    /// Create a new `Table` able to hold `capacity` elements without growing,
+    /// hashing keys with `hash_builder`.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut table = Self::with_hasher(hash_builder);
+        table.elements = RawTable::with_slots(table.slots_for(capacity));
+        table
+    }
+    /// Create a new `Table` with `slots` slots, hashing keys with `hash_builder`.
+    fn with_slots_and_hasher(slots: usize, hash_builder: S) -> Self {
+        Self {
+            elements: RawTable::with_slots(slots),
+            rehash: None,
+            len: 0,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            resizes: 0,
+            seed: 0,
+            reseeds: 0,
+            reseed_len: 0,
+            hash_builder,
+        }
+    }
+    /// Get a reference to the table's `BuildHasher`.
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+    /// Get the number of elements `slots` slots hold under the maximum load factor.
+    fn capacity_of(&self, slots: usize) -> usize {
+        (slots as f64 * self.max_load_factor).floor() as usize
+    }
+    /// Get the number of slots needed to hold `len` elements.
+    fn slots_for(&self, len: usize) -> usize {
+        let mut slots = slot_count((len as f64 / self.max_load_factor).ceil() as usize);
+        while self.capacity_of(slots) < len {
+            slots *= 2;
+        }
+        slots
+    }
+    /// Hash the key.
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        seeded_hash(&self.hash_builder, self.seed, key)
+    }
+    /// Find where the key is stored, in the old slot array or the current one.
+    fn find<Q>(&self, key: &Q) -> Option<Location>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.len == 0 {
+            return None;
+        }
+        self.find_hashed(self.hash(key), key)
+    }
+    /// Find where the key with the given hash is stored.
+    fn find_hashed<Q>(&self, hash: u64, key: &Q) -> Option<Location>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(rehash) = &self.rehash {
+            if let Some(index) = rehash.elements.find(hash, |(k, _)| k.borrow() == key) {
+                return Some(Location { old: true, index });
+            }
+        }
+        self.elements
+            .find(hash, |(k, _)| k.borrow() == key)
+            .map(|index| Location { old: false, index })
+    }
+    /// Insert a new key-value pair into the table.
+    /// If the key is already present, its value is replaced and the old one returned.
+    /// The table grows first if the new entry would cross the maximum load factor.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.rehash_step();
+        let hash = self.hash(&key);
+        if let Some(location) = self.find_hashed(hash, &key) {
+            return Some(mem::replace(&mut self.pair_mut(location).1, value));
+        }
+        self.insert_new(hash, key, value);
+        None
+    }
+    /// Insert a key known to be absent, along with its hash, growing the
+    /// table first if needed. Returns where the pair was stored.
+    fn insert_new(&mut self, mut hash: u64, key: K, value: V) -> Location {
+        // `rehash_step` empties the old slots before the new ones run out of
+        // room, so no migration is in progress when this rehashes.
+        let slots = self.elements.len();
+        let crowded = self.elements.items + self.elements.tombstones + 1 > self.capacity();
+        if self.len + 1 > self.capacity() || (crowded && self.len > self.capacity() / 2) {
+            let slots = (slots * 2).max(self.slots_for(self.len + 1));
+            self.rehash(slots);
+        } else if crowded {
+            // Too many tombstones lengthen every probe: rebuild at the same size.
+            // With the table at most half full, the migration is done long
+            // before the next rehash, so it never has to be finished at once.
+            self.rehash(slots);
+        }
+        let (mut index, probed) = self.elements.find_free(hash);
+        // Reseeding rebuilds the whole table, so it waits for the table to
+        // double in length since the last time, in case the hasher ignores
+        // the seed and the keys keep colliding.
+        if probed > MAX_PROBE_GROUPS && self.len >= 2 * self.reseed_len {
+            self.reseed();
+            hash = self.hash(&key);
+            index = self.elements.find_free(hash).0;
+        }
+        self.elements.insert_at(index, hash, (key, value));
+        self.len += 1;
+        Location { old: false, index }
+    }
+    /// Rebuild the table at the same size with a fresh seed, right away.
+    fn reseed(&mut self) {
+        // Lookups hash once for both slot arrays, so the old one must be
+        // gone before the seed changes, and the new one filled right after.
+        self.finish_rehash();
+        self.seed = fresh_seed();
+        self.reseeds += 1;
+        self.reseed_len = self.len;
+        self.rehash(self.elements.len());
+        self.finish_rehash();
+    }
+    /// Get the number of times the table was rebuilt with a fresh seed after
+    /// an insert probed too far.
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+    /// Get the entry for the given key, to inspect or modify it in place.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        self.rehash_step();
+        let hash = self.hash(&key);
+        match self.find_hashed(hash, &key) {
+            Some(location) => Entry::Occupied(OccupiedEntry {
+                table: self,
+                location,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                table: self,
+                hash,
+                key,
+            }),
+        }
+    }
+    /// Get the value for the given key.
+    pub fn get<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+    /// Get the stored key and the value for the given key.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Result<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key)
+            .map(|location| {
+                let (k, v) = self.pair(location);
+                (k, v)
+            })
+            .ok_or(Error::KeyNotFound)
+    }
+    /// Check if the table contains the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+    /// Remove the key-value pair from the table.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+    /// Remove the key-value pair from the table, returning the stored key and the value.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Result<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.elements.len() == 0 {
+            return Err(Error::EmptyTable);
+        }
+        self.rehash_step();
+        match self.find(key) {
+            Some(location) => Ok(self.take(location)),
+            None => Err(Error::KeyNotFound),
+        }
+    }
+    /// Update the value for the given key.
+    pub fn update<Q>(&mut self, key: &Q) -> Result<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.elements.len() == 0 {
+            return Err(Error::EmptyTable);
+        }
+        self.rehash_step();
+        match self.find(key) {
+            Some(location) => Ok(&mut self.pair_mut(location).1),
+            None => Err(Error::KeyNotFound),
+        }
+    }
+    /// Resize the table to the new number of slots, rounded up to a power of
+    /// two and to enough slots for the stored elements.
+    /// Entries move to the new slots incrementally, over the next operations.
+    pub fn resize(&mut self, new_capacity: usize) -> Result<()> {
+        if new_capacity == 0 {
+            return Err(Error::InvalidCapacity);
+        }
+        self.rehash(slot_count(new_capacity).max(self.slots_for(self.len)));
+        Ok(())
+    }
+    /// Grow the table so it can hold `additional` more elements without growing again.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed > self.capacity() {
+            self.rehash(self.slots_for(needed));
+        }
+    }
+    /// Shrink the table to the fewest slots that keep it under the maximum load factor.
+    pub fn shrink_to_fit(&mut self) {
+        let slots = self.slots_for(self.len).max(GROUP_WIDTH);
+        if slots < self.elements.len() {
+            self.rehash(slots);
+        }
+    }
+    /// Start moving every entry into `slots` fresh slots.
+    /// A migration still in progress is finished first, which only happens
+    /// when `resize`, `reserve` or `shrink_to_fit` are called during one.
+    fn rehash(&mut self, slots: usize) {
+        self.finish_rehash();
+        self.resizes += 1;
+        let elements = mem::replace(&mut self.elements, RawTable::with_slots(slots));
+        if self.len > 0 {
+            self.rehash = Some(Rehash { elements, next: 0 });
+        }
+    }
+    /// Migrate the next few slots of the old array, if a rehash is in progress.
+    ///
+    /// Every operation uses up at most one slot of the room left under the
+    /// maximum load factor once all entries have moved, so migrating the old
+    /// slots in as many steps as there is room left finishes the migration
+    /// before the new slots fill up. The step never grows as the migration
+    /// goes on, so the work done by a single operation stays bounded.
+    fn rehash_step(&mut self) {
+        let room = self
+            .capacity()
+            .saturating_sub(self.len + self.elements.tombstones);
+        let Some(rehash) = self.rehash.as_mut() else {
+            return;
+        };
+        let remaining = rehash.elements.len() - rehash.next;
+        let step = REHASH_STEP.max(remaining.div_ceil(room.max(1)));
+        let end = (rehash.next + step).min(rehash.elements.len());
+        for index in rehash.next..end {
+            // Hash before taking the pair, so a panicking `Hash` leaves it
+            // in its old slot instead of dropping it.
+            if let Some((key, _)) = &rehash.elements.slots[index] {
+                let hash = seeded_hash(&self.hash_builder, self.seed, key);
+                let pair = rehash.elements.take(index);
+                self.elements.insert(hash, pair);
+            }
+        }
+        rehash.next = end;
+        if end == rehash.elements.len() {
+            self.rehash = None;
+        }
+    }
+    /// Migrate every remaining slot of a rehash in progress right away.
+    pub fn finish_rehash(&mut self) {
+        while self.rehash.is_some() {
+            self.rehash_step();
+        }
+    }
+    /// Check if the table is migrating entries to a new slot array.
+    pub fn is_rehashing(&self) -> bool {
+        self.rehash.is_some()
+    }
+    /// Get the progress of a rehash in progress, as the number of old slots
+    /// already migrated and the total number of old slots.
+    pub fn rehash_progress(&self) -> Option<(usize, usize)> {
+        self.rehash
+            .as_ref()
+            .map(|rehash| (rehash.next, rehash.elements.len()))
+    }
+    /// Gather statistics about how the table is filled.
+    /// Every stored key is looked up again, so this takes time linear in its length.
+    pub fn stats(&self) -> TableStats {
+        let mut histogram = Vec::new();
+        let mut occupied_slots = 0;
+        let mut tombstones = 0;
+        let old = self.rehash.as_ref().map(|rehash| &rehash.elements);
+        for raw in old.into_iter().chain([&self.elements]) {
+            occupied_slots += raw.items;
+            tombstones += raw.tombstones;
+            for (key, _) in raw.slots.iter().flatten() {
+                let (_, probed) = raw.probe_length(self.hash(key), |(k, _)| k == key);
+                if histogram.len() < probed {
+                    histogram.resize(probed, 0);
+                }
+                histogram[probed - 1] += 1;
+            }
+        }
+        let total: usize = histogram
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i + 1) * count)
+            .sum();
+        let slots = self.elements.len();
+        TableStats {
+            len: self.len,
+            capacity: self.capacity(),
+            slots,
+            load_factor: if slots == 0 {
+                0.0
+            } else {
+                self.len as f64 / slots as f64
+            },
+            occupied_slots,
+            tombstones,
+            max_probe_length: histogram.len(),
+            mean_probe_length: if self.len == 0 {
+                0.0
+            } else {
+                total as f64 / self.len as f64
+            },
+            probe_histogram: histogram,
+            resizes: self.resizes,
+            reseeds: self.reseeds,
+        }
+    }
+    /// Iterate over the key-value pairs of the table, in slot order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.all_slots().flatten(),
+            remaining: self.len,
+        }
+    }
+    /// Iterate over the key-value pairs of the table with mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let remaining = self.len;
+        IterMut {
+            inner: self.all_slots_mut().flatten(),
+            remaining,
+        }
+    }
+    /// Iterate over the keys of the table.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+    /// Iterate over the values of the table.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+    /// Iterate over mutable references to the values of the table.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+    /// Remove every key-value pair from the table, yielding them.
+    /// The table keeps its current slots, and drops any rehash in progress.
+    pub fn drain(&mut self) -> Drain<'_, K, V, S> {
+        let old = self
+            .rehash
+            .take()
+            .map_or_else(Vec::new, |rehash| rehash.elements.slots);
+        Drain {
+            old: old.into_iter().flatten(),
+            table: self,
+            index: 0,
+        }
+    }
+    /// Keep only the key-value pairs for which `f` returns true.
+    /// If `f` panics, the pairs it already rejected stay removed and the
+    /// table is left consistent.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        // `extract_if` counts every removal as it happens, so a panic in `f`
+        // can't leave `len` out of step with the slots.
+        self.extract_if(|key, value| !f(key, value)).for_each(drop);
+    }
+    /// Remove and yield the key-value pairs for which `predicate` returns true.
+    pub fn extract_if<F>(&mut self, predicate: F) -> ExtractIf<'_, K, V, S, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            old: self.rehash.is_some(),
+            table: self,
+            index: 0,
+            predicate,
+        }
+    }
+    /// Get the number of elements in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Get the number of elements the table can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.capacity_of(self.elements.len())
+    }
+    /// Get the maximum ratio of elements to slots before the table grows.
+    pub fn max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+    /// Set the maximum ratio of elements to slots before the table grows.
+    /// It must be above 0 and below 1, so probes always find a free slot.
+    /// The table starts growing right away if it is already over the new limit.
+    pub fn set_max_load_factor(&mut self, max_load_factor: f64) -> Result<()> {
+        if !(max_load_factor > 0.0 && max_load_factor < 1.0) {
+            return Err(Error::InvalidLoadFactor);
+        }
+        self.max_load_factor = max_load_factor;
+        if self.len > self.capacity() {
+            self.rehash(self.slots_for(self.len));
+        }
+        Ok(())
+    }
+}
+/// Default implementation for `Table`.
+impl<K, V, S> Default for Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Self::with_slots_and_hasher(64, S::default())
+    }
+}
+
+impl<K, V, S> Debug for Table<K, V, S>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_map()
+            .entries(self.all_slots().flatten().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+/// Two tables are equal when they hold the same keys mapped to equal values.
+impl<K, V, S> PartialEq for Table<K, V, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_ok_and(|v| v == value))
+    }
+}
+
+impl<K, V, S> Eq for Table<K, V, S>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+/// Index the table by key.
+///
+/// # Panics
+///
+/// Panics if the key is not present in the table.
+impl<K, Q, V, S> Index<&Q> for Table<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in table")
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut table = Self::with_hasher(S::default());
+        table.extend(iter);
+        table
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for Table<K, V, S>
+where
+    K: Hash + Eq + Copy,
+    V: Copy,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(key, value)| (*key, *value)));
+    }
+}
+
+impl<K, V, S> IntoIterator for Table<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let old = self
+            .rehash
+            .map_or_else(Vec::new, |rehash| rehash.elements.slots);
+        IntoIter {
+            inner: old.into_iter().chain(self.elements.slots).flatten(),
+            remaining: self.len,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut Table<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    /// Hasher that uses integer keys as their own hash.
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 << 8) | *byte as u64;
+            }
+        }
+        fn write_u64(&mut self, n: u64) {
+            self.0 = n;
+        }
+    }
+
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[test]
+    fn test_hash_table_ops() {
+        let mut table = Table::new(16);
+        table.insert("key1", "value1");
+        assert_eq!(table.get(&"key1").unwrap(), &"value1");
+        table.remove(&"key1").unwrap();
+        assert!(table.get(&"key1").is_err());
+        table.insert("key2", "value2");
+        if let Ok(v) = table.update(&"key2") {
+            *v = "value3";
+        }
+        assert_eq!(table.get(&"key2").unwrap(), &"value3");
+    }
+
+    #[test]
+    fn test_hash_table_collisions() {
+        // A single group forces every key into the same probe window.
+        let mut table = Table::new(1);
+        table.insert("key1", 1);
+        table.insert("key2", 2);
+        table.insert("key3", 3);
+        assert_eq!(table.get(&"key1").unwrap(), &1);
+        assert_eq!(table.get(&"key2").unwrap(), &2);
+        assert_eq!(table.get(&"key3").unwrap(), &3);
+        assert_eq!(table.elements.len(), GROUP_WIDTH);
+        table.insert("key2", 20);
+        assert_eq!(table.get(&"key2").unwrap(), &20);
+        *table.update(&"key3").unwrap() = 30;
+        assert_eq!(table.remove(&"key1").unwrap(), 1);
+        assert!(table.get(&"key1").is_err());
+        assert_eq!(table.get(&"key2").unwrap(), &20);
+        assert_eq!(table.get(&"key3").unwrap(), &30);
+        table.resize(8).unwrap();
+        assert_eq!(table.get(&"key2").unwrap(), &20);
+        assert_eq!(table.get(&"key3").unwrap(), &30);
+    }
+
+    #[test]
+    fn test_hash_table_growth() {
+        let mut table = Table::with_capacity(10);
+        let capacity = table.capacity();
+        assert!(capacity >= 10);
+        for i in 0..10 {
+            table.insert(i, i.to_string());
+        }
+        assert_eq!(table.capacity(), capacity);
+        for i in 10..1000 {
+            table.insert(i, i.to_string());
+        }
+        assert_eq!(table.len(), 1000);
+        assert!(table.len() <= table.capacity());
+        assert!(table.len() as f64 / table.elements.len() as f64 <= table.max_load_factor());
+        for i in 0..990 {
+            table.remove(&i).unwrap();
+        }
+        table.shrink_to_fit();
+        assert!(table.capacity() < 100);
+        assert!(table.len() <= table.capacity());
+        assert_eq!(table.get(&995).unwrap(), "995");
+        table.reserve(500);
+        assert!(table.capacity() >= 510);
+        table.set_max_load_factor(0.9).unwrap();
+        table.shrink_to_fit();
+        assert_eq!(table.elements.len(), 16);
+        assert_eq!(table.get(&999).unwrap(), "999");
+
+        let mut table = Table::new(0);
+        table.insert("key1", "value1");
+        assert_eq!(table.get(&"key1").unwrap(), &"value1");
+    }
+
+    #[test]
+    fn test_hash_table_incremental_rehash() {
+        let mut table = Table::new(64);
+        for i in 0..40 {
+            table.insert(i, i);
+        }
+        assert!(!table.is_rehashing());
+        table.resize(256).unwrap();
+        assert_eq!(table.rehash_progress(), Some((0, 64)));
+        for i in 0..40 {
+            assert_eq!(table.get(&i).unwrap(), &i);
+        }
+        assert_eq!(table.iter().count(), 40);
+
+        let mut migrated = 0;
+        for i in 40..50 {
+            table.insert(i, i);
+            let (done, total) = table.rehash_progress().unwrap();
+            assert!(done > migrated && total == 64);
+            migrated = done;
+        }
+        *table.update(&3).unwrap() += 100;
+        assert_eq!(table.remove(&4).unwrap(), 4);
+        *table.entry(5).or_default() += 100;
+        assert_eq!(table.len(), 49);
+        let mut cloned = table.clone();
+        assert_eq!(cloned, table);
+        assert_eq!(table.iter().count(), 49);
+        assert_eq!(table.values_mut().count(), 49);
+
+        while table.is_rehashing() {
+            table.insert(0, 0);
+        }
+        assert_eq!(table.rehash_progress(), None);
+        assert_eq!(table.get(&3).unwrap(), &103);
+        assert_eq!(table.get(&5).unwrap(), &105);
+        assert!(table.get(&4).is_err());
+        assert_eq!(table.len(), 49);
+
+        assert!(cloned.is_rehashing());
+        cloned.finish_rehash();
+        assert!(!cloned.is_rehashing());
+        assert_eq!(cloned, table);
+    }
+
+    #[test]
+    fn test_hash_table_churn_rehash_is_bounded() {
+        // Get the number of old slots migrated between two progress reports.
+        fn migrated(before: Option<(usize, usize)>, after: Option<(usize, usize)>) -> usize {
+            match (before, after) {
+                (Some((from, _)), Some((to, _))) if to >= from => to - from,
+                (Some((from, total)), after) => total - from + after.map_or(0, |(to, _)| to),
+                (None, after) => after.map_or(0, |(to, _)| to),
+            }
+        }
+
+        for len in [12, 48, 90, 200, 700] {
+            let mut table = Table::with_capacity(len);
+            for i in 0..len {
+                table.insert(i, i);
+            }
+            let resizes = table.stats().resizes;
+            // Removing and inserting keeps the length steady but leaves
+            // tombstones behind, which keep forcing rebuilds.
+            for i in len..len * 40 {
+                table.remove(&(i - len)).unwrap();
+                let before = table.rehash_progress();
+                table.insert(i, i);
+                let after = table.rehash_progress();
+                assert!(migrated(before, after) <= REHASH_STEP);
+                assert_eq!(table.len(), len);
+            }
+            assert!(table.stats().resizes > resizes);
+            assert!((len * 39..len * 40).all(|i| table.get(&i).unwrap() == &i));
+        }
+    }
+
+    #[test]
+    fn test_hash_table_bulk_ops_while_rehashing() {
+        let fresh = || {
+            let mut table = Table::new(16);
+            for i in 0..12 {
+                table.insert(i, i);
+            }
+            table.resize(64).unwrap();
+            table.insert(12, 12);
+            assert!(table.is_rehashing());
+            table
+        };
+
+        let mut table = fresh();
+        table.retain(|k, _| k % 2 == 0);
+        assert_eq!(table.len(), 7);
+        assert_eq!(table.iter().count(), 7);
+
+        let mut table = fresh();
+        assert_eq!(table.extract_if(|k, _| k % 3 == 0).count(), 5);
+        assert_eq!(table.len(), 8);
+        assert!(table.get(&3).is_err());
+        assert_eq!(table.get(&4).unwrap(), &4);
+
+        let mut table = fresh();
+        assert_eq!(table.drain().count(), 13);
+        assert!(table.is_empty() && !table.is_rehashing());
+
+        let mut owned: Vec<_> = fresh().into_iter().map(|(k, _)| k).collect();
+        owned.sort();
+        assert_eq!(owned, (0..13).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_hash_table_hashers() {
+        let mut table: Table<u64, &str, BuildHasherDefault<IdentityHasher>> =
+            Table::with_capacity_and_hasher(4, BuildHasherDefault::default());
+        // Small keys share the first group and the same control byte,
+        // so only comparing the keys tells them apart.
+        let slots = table.elements.len() as u64;
+        table.insert(1, "one");
+        table.insert(1 + slots, "also one");
+        table.insert(2, "two");
+        assert_eq!(table.elements.slots.iter().flatten().count(), 3);
+        assert_eq!(table.get(&1).unwrap(), &"one");
+        assert_eq!(table.get(&(1 + slots)).unwrap(), &"also one");
+        assert!(table.get(&(2 + slots)).is_err());
+        assert_eq!(table.get(&2).unwrap(), &"two");
+
+        let mut table: Table<&str, i32, RandomState> = Table::with_hasher(RandomState::new());
+        table.insert("key1", 1);
+        assert_eq!(table.get(&"key1").unwrap(), &1);
+        let default: Table<&str, i32, BuildHasherDefault<IdentityHasher>> = Table::default();
+        assert!(default.get(&"key1").is_err());
+    }
+
+    #[test]
+    fn test_hash_table_unbounded_elements() {
+        use std::sync::Mutex;
+
+        /// Key type that is neither `Clone` nor `Debug`.
+        #[derive(PartialEq, Eq, Hash)]
+        struct Name(&'static str);
+
+        let mut handlers: Table<Name, Box<dyn Fn(i32) -> i32>> = Table::new(1);
+        handlers.insert(Name("double"), Box::new(|x| x * 2));
+        handlers.insert(Name("square"), Box::new(|x| x * x));
+        handlers.resize(16).unwrap();
+        assert_eq!(handlers.get(&Name("double")).unwrap()(4), 8);
+        assert_eq!(handlers.get(&Name("square")).unwrap()(4), 16);
+
+        let mut counters: Table<&str, Mutex<i32>> = Table::default();
+        counters.insert("hits", Mutex::new(0));
+        *counters.get(&"hits").unwrap().lock().unwrap() += 1;
+        assert_eq!(*counters.remove(&"hits").unwrap().lock().unwrap(), 1);
+
+        let mut table = Table::new(4);
+        table.insert("key1", vec![1]);
+        let mut cloned = table.clone();
+        cloned.update(&"key1").unwrap().push(2);
+        assert_eq!(table.get(&"key1").unwrap(), &vec![1]);
+        assert_eq!(cloned.get(&"key1").unwrap(), &vec![1, 2]);
+    }
+
+    #[test]
+    fn test_hash_table_std_parity() {
+        let mut table: Table<String, i32> = Table::new(8);
+        assert_eq!(table.insert("one".to_string(), 1), None);
+        assert_eq!(table.insert("one".to_string(), 11), Some(1));
+        table.insert("two".to_string(), 2);
+        assert_eq!(table.get("one").unwrap(), &11);
+        assert!(table.contains_key("two"));
+        assert!(!table.contains_key("three"));
+        let (key, value) = table.get_key_value("two").unwrap();
+        assert_eq!((key.as_str(), *value), ("two", 2));
+        assert_eq!(table["two"], 2);
+        *table.update("two").unwrap() += 1;
+        assert_eq!(table.remove("two").unwrap(), 3);
+        assert!(table.get_key_value("two").is_err());
+
+        let collected: Table<&str, i32> = vec![("a", 1), ("b", 2)].into_iter().collect();
+        let mut extended = Table::new(1);
+        extended.extend([(&"b", &2)]);
+        extended.extend(vec![("a", 1)]);
+        assert_eq!(collected, extended);
+        extended.insert("a", 3);
+        assert_ne!(collected, extended);
+        extended.insert("c", 1);
+        assert_ne!(collected, extended);
+
+        let single: Table<&str, i32> = [("a", 1)].into_iter().collect();
+        assert_eq!(format!("{:?}", single), r#"{"a": 1}"#);
+    }
+
+    #[test]
+    fn test_hash_table_stats() {
+        let mut table = Table::new(0);
+        assert_eq!(table.stats(), TableStats::default());
+        for i in 0..100 {
+            table.insert(i, i);
+        }
+        let stats = table.stats();
+        assert_eq!(stats.len, 100);
+        assert_eq!(stats.slots, table.elements.len());
+        assert_eq!(stats.capacity, table.capacity());
+        assert_eq!(stats.load_factor, 100.0 / stats.slots as f64);
+        assert_eq!(stats.occupied_slots, 100);
+        assert_eq!(stats.probe_histogram.iter().sum::<usize>(), 100);
+        assert_eq!(stats.max_probe_length, stats.probe_histogram.len());
+        assert!(stats.mean_probe_length >= 1.0);
+        assert!(stats.mean_probe_length <= stats.max_probe_length as f64);
+        assert!(stats.resizes >= 4);
+
+        // Colliding keys probe further and further.
+        let mut table: Table<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            Table::with_capacity_and_hasher(20, BuildHasherDefault::default());
+        let slots = table.elements.len() as u64;
+        for i in 0..20 {
+            table.insert(i * slots, i);
+        }
+        let stats = table.stats();
+        assert_eq!(stats.probe_histogram, vec![8, 8, 4]);
+        assert_eq!(stats.max_probe_length, 3);
+        assert_eq!(stats.mean_probe_length, 1.8);
+        assert_eq!(stats.resizes, 0);
+
+        table.resize(64).unwrap();
+        table.remove(&0).unwrap();
+        let stats = table.stats();
+        assert_eq!(stats.resizes, 1);
+        assert_eq!(stats.len, 19);
+        assert_eq!(stats.occupied_slots, 19);
+    }
+
+    #[test]
+    fn test_hash_table_reseed() {
+        let table: Table<u64, u64> = (0..1000).map(|i| (i, i)).collect();
+        assert_eq!(table.reseeds(), 0);
+
+        // Keys sharing their low bits all probe from the same group.
+        let mut table: Table<u64, u64, BuildHasherDefault<IdentityHasher>> =
+            Table::with_hasher(BuildHasherDefault::default());
+        for i in 0..1000 {
+            table.insert(i << 32, i);
+        }
+        assert_eq!(table.reseeds(), 1);
+        let stats = table.stats();
+        assert_eq!(stats.reseeds, 1);
+        assert!(stats.max_probe_length <= MAX_PROBE_GROUPS);
+        assert_eq!(table.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(table.get(&(i << 32)).unwrap(), &i);
+        }
+        assert!(table.get(&1).is_err());
+
+        // A hasher mapping every key to one hash defeats any seed, so the
+        // table only reseeds again once its length doubles.
+        let mut table: Table<u64, u64, BuildHasherDefault<ConstantHasher>> =
+            Table::with_hasher(BuildHasherDefault::default());
+        for i in 0..600 {
+            table.entry(i).or_insert(i);
+        }
+        assert_eq!(table.reseeds(), 3);
+        assert!((0..600).all(|i| table.get(&i).ok() == Some(&i)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hash_table_index_missing_key() {
+        let table: Table<&str, i32> = Table::new(8);
+        let _ = table["missing"];
+    }
+
+    #[test]
+    fn test_hash_table_errors() {
+        let mut table: Table<&str, &str> = Table::new(16);
+        assert!(table.get(&"key1").is_err());
+        assert!(table.remove(&"key1").is_err());
+        assert!(table.update(&"key1").is_err());
+        assert!(table.resize(0).is_err());
+        assert!(table.set_max_load_factor(0.0).is_err());
+        assert!(table.set_max_load_factor(f64::NAN).is_err());
+        assert!(table.set_max_load_factor(1.0).is_err());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,263 @@
+//! Control-byte storage behind `Table` and `Set`.
+//!
+//! Every slot has a control byte in a separate array: `EMPTY`, `DELETED`, or
+//! the top 7 bits of the hash of the item it holds. Probing loads 8 control
+//! bytes at once as a `u64` and matches them with word-at-a-time bit tricks,
+//! so most slots holding other keys are skipped without reading the keys.
+
+/// Number of control bytes probed together, as one machine word.
+pub(crate) const GROUP_WIDTH: usize = 8;
+/// Control byte of a slot that has held no pair since the last rehash.
+const EMPTY: u8 = 0xFF;
+/// Control byte of a slot whose pair was removed. Probes go on past it.
+const DELETED: u8 = 0x80;
+/// The lowest bit of every byte of a group.
+const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+/// The highest bit of every byte of a group.
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+pub(super) type Slot<T> = Option<T>;
+
+/// Get the 7-bit hash fragment stored in the control byte of a full slot.
+fn h2(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+/// Round a slot count up to a whole, power-of-two number of groups.
+pub(crate) fn slot_count(slots: usize) -> usize {
+    match slots {
+        0 => 0,
+        _ => slots.max(GROUP_WIDTH).next_power_of_two(),
+    }
+}
+
+/// The control bytes of a group, loaded as one little-endian word.
+#[derive(Clone, Copy)]
+struct Group(u64);
+
+impl Group {
+    /// Load the control bytes of the group at `index`.
+    fn load(ctrl: &[u8], index: usize) -> Self {
+        let start = index * GROUP_WIDTH;
+        let mut bytes = [0; GROUP_WIDTH];
+        bytes.copy_from_slice(&ctrl[start..start + GROUP_WIDTH]);
+        Self(u64::from_le_bytes(bytes))
+    }
+    /// Match the bytes equal to `byte`.
+    /// A byte following a true match may match falsely, so callers compare keys.
+    fn match_byte(self, byte: u8) -> BitMask {
+        let cmp = self.0 ^ (LOW_BITS * byte as u64);
+        BitMask(cmp.wrapping_sub(LOW_BITS) & !cmp & HIGH_BITS)
+    }
+    /// Match the `EMPTY` bytes, the only ones with both top bits set.
+    fn match_empty(self) -> BitMask {
+        BitMask(self.0 & (self.0 << 1) & HIGH_BITS)
+    }
+    /// Match the `EMPTY` and `DELETED` bytes, the only ones with the top bit set.
+    fn match_empty_or_deleted(self) -> BitMask {
+        BitMask(self.0 & HIGH_BITS)
+    }
+}
+
+/// The positions matched in a group, as the top bit of each matching byte.
+struct BitMask(u64);
+
+impl BitMask {
+    /// Check if any position matched.
+    fn any_set(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let position = self.0.trailing_zeros() as usize / 8;
+        self.0 &= self.0 - 1;
+        Some(position)
+    }
+}
+
+/// One array of slots along with their control bytes. `Table` stores its
+/// key-value pairs as the items, and `Set` its values.
+#[derive(Clone)]
+pub(crate) struct RawTable<T> {
+    ctrl: Vec<u8>,
+    pub(crate) slots: Vec<Slot<T>>,
+    /// Number of full slots.
+    pub(crate) items: usize,
+    /// Number of `DELETED` slots, which slow down probing until a rehash.
+    pub(crate) tombstones: usize,
+}
+
+impl<T> RawTable<T> {
+    /// Allocate `slots` empty slots, which must be 0 or a `slot_count`.
+    pub(crate) fn with_slots(slots: usize) -> Self {
+        Self {
+            ctrl: vec![EMPTY; slots],
+            slots: std::iter::repeat_with(|| None).take(slots).collect(),
+            items: 0,
+            tombstones: 0,
+        }
+    }
+    /// Get the number of slots.
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+    /// Iterate over the groups probed for a hash, each one once.
+    /// Triangular steps visit every group when their number is a power of two.
+    fn probe(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let groups = self.slots.len() / GROUP_WIDTH;
+        let mask = groups.wrapping_sub(1);
+        let mut group = hash as usize & mask;
+        (0..groups).map(move |stride| {
+            group = (group + stride) & mask;
+            group
+        })
+    }
+    /// Find the slot holding an item with the given hash for which `eq` holds.
+    pub(crate) fn find<F>(&self, hash: u64, mut eq: F) -> Option<usize>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.probe_length(hash, &mut eq).0
+    }
+    /// Find the slot holding an item with the given hash for which `eq` holds,
+    /// along with the number of groups probed.
+    pub(crate) fn probe_length<F>(&self, hash: u64, mut eq: F) -> (Option<usize>, usize)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        if self.items == 0 {
+            return (None, 0);
+        }
+        let h2 = h2(hash);
+        for (probed, group) in self.probe(hash).enumerate() {
+            let ctrl = Group::load(&self.ctrl, group);
+            for position in ctrl.match_byte(h2) {
+                let index = group * GROUP_WIDTH + position;
+                if let Some(item) = &self.slots[index] {
+                    if eq(item) {
+                        return (Some(index), probed + 1);
+                    }
+                }
+            }
+            // An empty slot ends the probe: the item would have been put there.
+            if ctrl.match_empty().any_set() {
+                return (None, probed + 1);
+            }
+        }
+        (None, self.slots.len() / GROUP_WIDTH)
+    }
+    /// Store an item in the first free slot of its probe sequence.
+    /// The table must have a free slot and must not hold the item.
+    pub(crate) fn insert(&mut self, hash: u64, item: T) -> usize {
+        let (index, _) = self.find_free(hash);
+        self.insert_at(index, hash, item);
+        index
+    }
+    /// Find the first free slot of the probe sequence for a hash,
+    /// along with the number of groups probed. The table must have a free slot.
+    pub(crate) fn find_free(&self, hash: u64) -> (usize, usize) {
+        self.probe(hash)
+            .enumerate()
+            .find_map(|(probed, group)| {
+                let position = Group::load(&self.ctrl, group)
+                    .match_empty_or_deleted()
+                    .next()?;
+                Some((group * GROUP_WIDTH + position, probed + 1))
+            })
+            .expect("table has a free slot")
+    }
+    /// Store an item in a free slot found by `find_free` for the same hash.
+    pub(crate) fn insert_at(&mut self, index: usize, hash: u64, item: T) {
+        if self.ctrl[index] == DELETED {
+            self.tombstones -= 1;
+        }
+        self.ctrl[index] = h2(hash);
+        self.slots[index] = Some(item);
+        self.items += 1;
+    }
+    /// Remove the item stored in a full slot.
+    pub(crate) fn take(&mut self, index: usize) -> T {
+        // A group that still has an empty slot has always had one, so no probe
+        // ever went past it and the slot can be emptied instead of deleted.
+        let group = Group::load(&self.ctrl, index / GROUP_WIDTH);
+        self.ctrl[index] = if group.match_empty().any_set() {
+            EMPTY
+        } else {
+            self.tombstones += 1;
+            DELETED
+        };
+        self.items -= 1;
+        self.slots[index]
+            .take()
+            .expect("control byte marks a full slot")
+    }
+    /// Empty every slot, keeping the allocation.
+    pub(crate) fn clear(&mut self) {
+        self.ctrl.fill(EMPTY);
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.items = 0;
+        self.tombstones = 0;
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(bytes: [u8; GROUP_WIDTH]) -> Group {
+        Group::load(&bytes, 0)
+    }
+
+    #[test]
+    fn test_group_matching() {
+        let ctrl = group([0x12, EMPTY, 0x34, DELETED, 0x12, 0x00, EMPTY, 0x7F]);
+        assert!(ctrl.match_byte(0x12).collect::<Vec<_>>().starts_with(&[0]));
+        assert!(ctrl.match_byte(0x12).any(|position| position == 4));
+        assert_eq!(ctrl.match_byte(0x7F).collect::<Vec<_>>(), vec![7]);
+        assert_eq!(ctrl.match_empty().collect::<Vec<_>>(), vec![1, 6]);
+        assert_eq!(
+            ctrl.match_empty_or_deleted().collect::<Vec<_>>(),
+            vec![1, 3, 6]
+        );
+        assert!(!group([0; GROUP_WIDTH]).match_empty().any_set());
+        assert_eq!(slot_count(0), 0);
+        assert_eq!(slot_count(1), GROUP_WIDTH);
+        assert_eq!(slot_count(100), 128);
+    }
+
+    #[test]
+    fn test_raw_table_probing() {
+        let mut raw = RawTable::with_slots(16);
+        // Same group and same fragment: the keys themselves tell them apart.
+        let hash = 0x0100_0000_0000_0001;
+        for key in 0..10 {
+            raw.insert(hash, (key, key * 10));
+        }
+        for key in 0..10 {
+            let index = raw.find(hash, |(k, _)| *k == key).unwrap();
+            assert_eq!(raw.slots[index], Some((key, key * 10)));
+        }
+        assert_eq!(raw.probe_length(hash, |(k, _)| *k == 9).1, 2);
+        assert!(raw.find(hash, |(k, _)| *k == 10).is_none());
+
+        // Removing from a full group leaves a tombstone so later keys stay reachable.
+        let index = raw.find(hash, |(k, _)| *k == 0).unwrap();
+        assert_eq!(raw.take(index), (0, 0));
+        assert_eq!(raw.tombstones, 1);
+        assert!(raw.find(hash, |(k, _)| *k == 9).is_some());
+        raw.insert(hash, (0, 1));
+        assert_eq!(raw.tombstones, 0);
+        raw.clear();
+        assert_eq!(raw.items, 0);
+        assert!(raw.find(hash, |(k, _)| *k == 1).is_none());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,89 @@
+use super::Table;
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt::{Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// Largest number of elements preallocated from a size hint, so a
+/// deserializer can't claim a huge length to exhaust memory.
+const MAX_PREALLOCATED: usize = 4096;
+
+/// Serialize a `Table` as a map of its pairs.
+impl<K, V, S> Serialize for Table<K, V, S>
+where
+    K: Hash + Eq + Serialize,
+    V: Serialize,
+    S: BuildHasher,
+{
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Deserialize a `Table` from a map. A repeated key keeps its last value.
+impl<'de, K, V, S> Deserialize<'de> for Table<K, V, S>
+where
+    K: Hash + Eq + Deserialize<'de>,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(TableVisitor(PhantomData))
+    }
+}
+
+struct TableVisitor<K, V, S>(PhantomData<Table<K, V, S>>);
+
+impl<'de, K, V, S> Visitor<'de> for TableVisitor<K, V, S>
+where
+    K: Hash + Eq + Deserialize<'de>,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    type Value = Table<K, V, S>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let capacity = access.size_hint().unwrap_or(0).min(MAX_PREALLOCATED);
+        let mut table = Table::with_capacity_and_hasher(capacity, S::default());
+        while let Some((key, value)) = access.next_entry()? {
+            table.insert(key, value);
+        }
+        Ok(table)
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_serde() {
+        let table: Table<String, Vec<u32>> = (0..20)
+            .map(|i| (format!("key{}", i), vec![i; i as usize % 3]))
+            .collect();
+        let json = serde_json::to_string(&table).unwrap();
+        let restored: Table<String, Vec<u32>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, table);
+
+        let table: Table<String, i32> =
+            serde_json::from_str(r#"{"a": 1, "b": 2, "a": 3}"#).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table["a"], 3);
+        assert_eq!(
+            serde_json::to_string(&Table::<u8, u8>::default()).unwrap(),
+            "{}"
+        );
+        assert!(serde_json::from_str::<Table<String, i32>>("[1, 2]").is_err());
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,33 @@
+//! Statistics about the layout of a `Table`.
+
+/// A snapshot of how a `Table` is filled, returned by `Table::stats`.
+///
+/// Probe lengths count the groups of 8 slots a lookup reads before it finds
+/// a key, so a key found in its first group has a probe length of 1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableStats {
+    /// Number of elements in the table.
+    pub len: usize,
+    /// Number of elements the table holds before it grows.
+    pub capacity: usize,
+    /// Number of slots in the current slot array.
+    pub slots: usize,
+    /// Ratio of elements to slots.
+    pub load_factor: f64,
+    /// Number of slots holding a pair, across the current slot array and
+    /// the one being migrated away from.
+    pub occupied_slots: usize,
+    /// Number of slots left behind by removals, until the next rehash.
+    pub tombstones: usize,
+    /// Longest probe length of any stored key.
+    pub max_probe_length: usize,
+    /// Mean probe length of the stored keys.
+    pub mean_probe_length: f64,
+    /// Number of keys for each probe length: entry `i` counts the keys
+    /// found after probing `i + 1` groups.
+    pub probe_histogram: Vec<usize>,
+    /// Number of times the table was resized or rebuilt.
+    pub resizes: usize,
+    /// Number of times the table was rebuilt with a fresh seed.
+    pub reseeds: usize,
+}
@@ -0,0 +1,441 @@
+mod sketch;
+
+use super::{Error, Result, Table};
+use crate::linked_lists::{Double, ExtNode};
+use sketch::FrequencySketch;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::RandomState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::rc::Rc;
+
+/// Node of a recency list, shared between the list and the table entry.
+type Link<K> = Rc<RefCell<ExtNode<Entry<K>>>>;
+/// Callback receiving the entries evicted to make room.
+type OnEvict<K, V> = Box<dyn FnMut(K, V)>;
+
+/// Share of the capacity given to the admission window, in percent.
+const WINDOW_PERCENT: usize = 1;
+/// Share of the main region given to the protected segment, in percent.
+const PROTECTED_PERCENT: usize = 80;
+
+/// The region of the cache holding a key, indexing its recency list.
+#[derive(Clone, Copy)]
+enum Region {
+    Window,
+    Probation,
+    Protected,
+}
+
+/// A key in a recency list, with the region whose list holds it. Keeping
+/// the region in the node lets an entry change region without a table probe.
+struct Entry<K> {
+    key: K,
+    region: Cell<Region>,
+}
+
+/// A cached value, with its node in its region's recency list.
+struct Slot<K, V> {
+    value: V,
+    node: Link<K>,
+}
+
+/// `TinyLfuCache` is a fixed-capacity cache admitting entries by frequency
+/// (W-TinyLFU).
+///
+/// New entries go to a small LRU window. An entry leaving the window only
+/// enters the main region if a count-min sketch of recent accesses says it
+/// is used more often than the main region's eviction victim, so a scan of
+/// one-off keys can't flush out the popular ones. The main region is a
+/// segmented LRU: entries start on probation, and are moved to the protected
+/// segment when accessed again. The sketch halves its counts periodically, so
+/// keys that stop being popular are eventually replaced.
+///
+/// Every entry lives in one `Table`, and each region orders its keys in a
+/// `Double` linked list, as `LruCache` does, so a hit costs a single table
+/// probe.
+pub struct TinyLfuCache<K, V, S = RandomState> {
+    table: Table<K, Slot<K, V>, S>,
+    /// Keys of each region, from least to most recently used.
+    orders: [Double<Entry<K>>; 3],
+    /// Most entries each region holds. Probation may use all of the main
+    /// region the protected segment leaves free.
+    capacities: [usize; 3],
+    sketch: FrequencySketch,
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+impl<K, V> TinyLfuCache<K, V, RandomState>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create a new `TinyLfuCache` holding at most `capacity` entries.
+    /// A cache with no capacity keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> TinyLfuCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Create a new `TinyLfuCache` holding at most `capacity` entries,
+    /// hashing keys with `hash_builder`.
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Self {
+        let window = match capacity {
+            0 => 0,
+            _ => (capacity * WINDOW_PERCENT / 100).max(1),
+        };
+        let main = capacity - window;
+        Self {
+            table: Table::with_capacity_and_hasher(capacity, hash_builder),
+            orders: [Double::new(), Double::new(), Double::new()],
+            capacities: [window, main, main * PROTECTED_PERCENT / 100],
+            sketch: FrequencySketch::new(capacity),
+            capacity,
+            hits: 0,
+            misses: 0,
+            on_evict: None,
+        }
+    }
+    /// Set a callback receiving every entry evicted to make room, or refused
+    /// admission to the main region. Entries taken out with `remove` are not
+    /// passed to it.
+    pub fn set_on_evict<F>(&mut self, on_evict: F)
+    where
+        F: FnMut(K, V) + 'static,
+    {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+    /// Get the number of entries in a region.
+    fn region_len(&self, region: Region) -> usize {
+        self.orders[region as usize].len()
+    }
+    /// Record an access to the key in the frequency sketch.
+    fn record<Q>(&mut self, key: &Q)
+    where
+        Q: Hash + ?Sized,
+    {
+        self.sketch.increment(self.table.hasher().hash_one(key));
+    }
+    /// Get the value for the given key, recording the access.
+    /// An entry on probation that is accessed again becomes protected.
+    pub fn get<Q>(&mut self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.record(key);
+        let protected = self.capacities[Region::Protected as usize];
+        let Ok(slot) = self.table.get(key) else {
+            self.misses += 1;
+            return Err(Error::KeyNotFound);
+        };
+        self.hits += 1;
+        match Self::region(&slot.node) {
+            Region::Probation if protected > 0 => {
+                Self::relink(&mut self.orders, &slot.node, Region::Protected);
+                // Make room by demoting the least recently used protected entry.
+                if self.orders[Region::Protected as usize].len() > protected {
+                    if let Some(node) = self.orders[Region::Protected as usize].front_node() {
+                        Self::relink(&mut self.orders, &node, Region::Probation);
+                    }
+                }
+            }
+            region => self.orders[region as usize].move_node_to_back(&slot.node),
+        }
+        Ok(&slot.value)
+    }
+    /// Get the region holding a node.
+    fn region(node: &Link<K>) -> Region {
+        RefCell::borrow(node).get_value().region.get()
+    }
+    /// Move a node to the back of another region's recency list.
+    fn relink(orders: &mut [Double<Entry<K>>; 3], node: &Link<K>, region: Region) {
+        orders[Self::region(node) as usize].unlink_node(node);
+        orders[region as usize].link_back(node);
+        RefCell::borrow(node).get_value().region.set(region);
+    }
+    /// Get the value for the given key without recording the access.
+    pub fn peek<Q>(&self, key: &Q) -> Result<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(key).map(|slot| &slot.value)
+    }
+    /// Check if the cache contains the key, without recording the access.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(key)
+    }
+    /// Insert a key-value pair into the admission window, recording the access.
+    /// If the window is full, its least recently used entry is admitted to
+    /// the main region or evicted.
+    /// If the key was already present, its old value is returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.record(&key);
+        if let Ok(slot) = self.table.update(&key) {
+            self.orders[Self::region(&slot.node) as usize].move_node_to_back(&slot.node);
+            return Some(mem::replace(&mut slot.value, value));
+        }
+        if self.capacity == 0 {
+            self.evicted(key, value);
+            return None;
+        }
+        if self.region_len(Region::Window) >= self.capacities[Region::Window as usize] {
+            if let Some((candidate, value)) = self.pop_lru(Region::Window) {
+                self.admit(candidate, value);
+            }
+        }
+        self.push(Region::Window, key, value);
+        None
+    }
+    /// Store an entry as the most recently used of a region.
+    fn push(&mut self, region: Region, key: K, value: V) {
+        let node = self.orders[region as usize].push_back_node(Entry {
+            key: key.clone(),
+            region: Cell::new(region),
+        });
+        self.table.insert(key, Slot { value, node });
+    }
+    /// Remove the least recently used entry of a region.
+    fn pop_lru(&mut self, region: Region) -> Option<(K, V)> {
+        let node = self.orders[region as usize].front_node()?;
+        self.orders[region as usize].unlink_node(&node);
+        let (key, slot) = self
+            .table
+            .remove_entry(&RefCell::borrow(&node).get_value().key)
+            .ok()?;
+        Some((key, slot.value))
+    }
+    /// Move an entry leaving the window to probation if the main region has
+    /// room, or if it is used more often than the region's eviction victim.
+    /// The loser of the comparison is evicted.
+    fn admit(&mut self, candidate: K, value: V) {
+        let main = self.capacities[Region::Probation as usize];
+        if self.region_len(Region::Probation) + self.region_len(Region::Protected) < main {
+            self.push(Region::Probation, candidate, value);
+            return;
+        }
+        let hasher = self.table.hasher();
+        let victim = match self.orders[Region::Probation as usize].front_node() {
+            Some(node) => hasher.hash_one(&RefCell::borrow(&node).get_value().key),
+            None => return self.evicted(candidate, value),
+        };
+        let candidate_hash = hasher.hash_one(&candidate);
+        if self.sketch.frequency(candidate_hash) > self.sketch.frequency(victim) {
+            if let Some((victim, victim_value)) = self.pop_lru(Region::Probation) {
+                self.evicted(victim, victim_value);
+            }
+            self.push(Region::Probation, candidate, value);
+        } else {
+            self.evicted(candidate, value);
+        }
+    }
+    /// Hand an evicted entry to the eviction callback.
+    fn evicted(&mut self, key: K, value: V) {
+        if let Some(on_evict) = self.on_evict.as_mut() {
+            on_evict(key, value);
+        }
+    }
+    /// Remove the key from the cache, returning its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Result<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.table.remove(key)?;
+        self.orders[Self::region(&slot.node) as usize].unlink_node(&slot.node);
+        Ok(slot.value)
+    }
+    /// Estimate how often the key was accessed recently.
+    pub fn frequency<Q>(&self, key: &Q) -> u8
+    where
+        Q: Hash + ?Sized,
+    {
+        self.sketch.frequency(self.table.hasher().hash_one(key))
+    }
+    /// Get the share of `get` calls that found their key, from 0 to 1.
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+    /// Get the number of `get` calls that found their key.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+    /// Get the number of `get` calls that missed.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+    /// Reset the hit and miss counts.
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+    /// Remove every entry from the cache, without calling the eviction callback.
+    /// Access frequencies and stats are kept.
+    pub fn clear(&mut self) {
+        self.table.drain().for_each(drop);
+        self.orders = [Double::new(), Double::new(), Double::new()];
+    }
+    /// Get the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+    /// Get the maximum number of entries the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<K, V, S> Debug for TinyLfuCache<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TinyLfuCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .field("hit_ratio", &self.hit_ratio())
+            .finish()
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::super::LruCache;
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_tinylfu_cache_ops() {
+        let mut cache = TinyLfuCache::new(10);
+        assert_eq!(cache.insert("a", 1), None);
+        assert_eq!(cache.insert("b", 2), None);
+        assert_eq!(cache.get("a").unwrap(), &1);
+        assert_eq!(cache.get("a").unwrap(), &1);
+        assert!(cache.get("z").is_err());
+        assert_eq!(cache.hits(), 2);
+        assert_eq!(cache.misses(), 1);
+        assert!((cache.hit_ratio() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(cache.frequency("a"), 3);
+
+        assert_eq!(cache.insert("a", 10), Some(1));
+        assert_eq!(cache.peek("a").unwrap(), &10);
+        assert!(cache.contains_key("b"));
+        assert_eq!(cache.remove("b").unwrap(), 2);
+        assert!(cache.remove("b").is_err());
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+        cache.reset_stats();
+        assert_eq!(cache.hit_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_tinylfu_cache_regions() {
+        let lens = |cache: &TinyLfuCache<i32, i32>| {
+            [Region::Window, Region::Probation, Region::Protected].map(|r| cache.region_len(r))
+        };
+        let mut cache = TinyLfuCache::new(10);
+        for key in 0..10 {
+            cache.insert(key, key);
+        }
+        assert_eq!(lens(&cache), [1, 9, 0]);
+        for key in 0..9 {
+            assert_eq!(cache.get(&key).unwrap(), &key);
+        }
+        // The eighth promotion overfills the protected segment, so it demotes
+        // the least recently used protected entry back to probation.
+        assert_eq!(lens(&cache), [1, 2, 7]);
+        let node = &cache.table.get(&0).unwrap().node;
+        assert!(matches!(
+            TinyLfuCache::<i32, i32>::region(node),
+            Region::Probation
+        ));
+        assert_eq!(cache.insert(9, 90), Some(9));
+        assert_eq!(lens(&cache), [1, 2, 7]);
+        assert_eq!(cache.len(), 10);
+        assert!((0..10).all(|key| cache.contains_key(&key)));
+        assert_eq!(cache.remove(&0).unwrap(), 0);
+        assert_eq!(lens(&cache), [1, 1, 7]);
+    }
+
+    #[test]
+    fn test_tinylfu_cache_eviction() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = TinyLfuCache::new(20);
+        let sink = evicted.clone();
+        cache.set_on_evict(move |key, _| sink.borrow_mut().push(key));
+        for i in 0..100 {
+            cache.insert(i, i);
+            cache.get(&(i % 7)).ok();
+        }
+        assert_eq!(cache.len(), 20);
+        // Every key is either still cached or was evicted exactly once.
+        let mut seen: Vec<i32> = evicted.borrow().clone();
+        seen.extend((0..100).filter(|i| cache.contains_key(i)));
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        // The keys read on every step were kept.
+        assert!((0..7).all(|i| cache.contains_key(&i)));
+
+        let mut empty = TinyLfuCache::new(0);
+        let sink = evicted.clone();
+        empty.set_on_evict(move |key, _| sink.borrow_mut().push(key));
+        empty.insert(500, 0);
+        assert!(empty.is_empty());
+        assert_eq!(evicted.borrow().last(), Some(&500));
+    }
+
+    #[test]
+    fn test_tinylfu_cache_scan_resistance() {
+        let mut cache = TinyLfuCache::new(100);
+        let mut lru = LruCache::new(100);
+        for round in 0..20 {
+            for key in 0..50 {
+                if cache.get(&key).is_err() {
+                    cache.insert(key, round);
+                }
+                if lru.get(&key).is_err() {
+                    lru.put(key, round);
+                }
+            }
+        }
+        // A scan of one-off keys flushes an LRU cache, but is not admitted
+        // over the popular keys.
+        for key in 1000..11_000 {
+            cache.insert(key, 0);
+            lru.put(key, 0);
+        }
+        assert_eq!((0..50).filter(|key| lru.contains_key(key)).count(), 0);
+        assert!((0..50).filter(|key| cache.contains_key(key)).count() >= 45);
+
+        cache.reset_stats();
+        for key in 0..50 {
+            cache.get(&key).ok();
+        }
+        assert!(cache.hit_ratio() >= 0.9);
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,115 @@
+//! Count-min sketch estimating how often keys were accessed.
+//!
+//! Each of `DEPTH` rows holds small saturating counters, and a key hash picks
+//! one counter per row. Collisions can only inflate a counter, so the
+//! smallest of a key's counters is the best estimate. Once the sketch has
+//! recorded `SAMPLE_FACTOR` accesses per counter of a row, every counter is
+//! halved, so old popularity fades and new keys can catch up.
+
+/// Number of rows, each indexed by a differently seeded hash.
+const DEPTH: usize = 4;
+/// Seeds mixed into the key hash to index each row.
+const SEEDS: [u64; DEPTH] = [
+    0x9e37_79b9_7f4a_7c15,
+    0xc2b2_ae3d_27d4_eb4f,
+    0x1656_67b1_9e37_79f9,
+    0x27d4_eb2f_1656_67c5,
+];
+/// Largest value of a counter, as with 4-bit counters.
+const MAX_COUNT: u8 = 15;
+/// Accesses recorded per counter of a row before the counters are halved.
+const SAMPLE_FACTOR: usize = 10;
+
+/// Scramble the bits of a hash (the splitmix64 finalizer).
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Approximate access counts of keys, by hash, with periodic aging.
+pub(super) struct FrequencySketch {
+    /// `DEPTH` rows of `width` counters each.
+    counters: Vec<u8>,
+    width: usize,
+    /// Accesses recorded since the counters were last halved.
+    additions: usize,
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    /// Create a sketch sized for a cache of `capacity` entries.
+    pub(super) fn new(capacity: usize) -> Self {
+        let width = capacity.max(16).next_power_of_two();
+        Self {
+            counters: vec![0; DEPTH * width],
+            width,
+            additions: 0,
+            sample_size: SAMPLE_FACTOR * width,
+        }
+    }
+    /// Get the counter of a hash in a row.
+    fn index(&self, hash: u64, row: usize) -> usize {
+        row * self.width + (mix(hash ^ SEEDS[row]) as usize & (self.width - 1))
+    }
+    /// Record an access to the key with the given hash.
+    pub(super) fn increment(&mut self, hash: u64) {
+        let mut added = false;
+        for row in 0..DEPTH {
+            let index = self.index(hash, row);
+            if self.counters[index] < MAX_COUNT {
+                self.counters[index] += 1;
+                added = true;
+            }
+        }
+        if added {
+            self.additions += 1;
+            if self.additions >= self.sample_size {
+                self.age();
+            }
+        }
+    }
+    /// Estimate the recent accesses to the key with the given hash.
+    pub(super) fn frequency(&self, hash: u64) -> u8 {
+        (0..DEPTH)
+            .map(|row| self.counters[self.index(hash, row)])
+            .min()
+            .unwrap_or(0)
+    }
+    /// Halve every counter.
+    fn age(&mut self) {
+        self.counters.iter_mut().for_each(|count| *count >>= 1);
+        self.additions /= 2;
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_sketch() {
+        let mut sketch = FrequencySketch::new(64);
+        for _ in 0..5 {
+            sketch.increment(1);
+        }
+        sketch.increment(2);
+        assert_eq!(sketch.frequency(1), 5);
+        assert_eq!(sketch.frequency(2), 1);
+        assert_eq!(sketch.frequency(3), 0);
+        for _ in 0..100 {
+            sketch.increment(1);
+        }
+        assert_eq!(sketch.frequency(1), MAX_COUNT);
+
+        // The access completing a sample halves every count.
+        sketch.additions = sketch.sample_size - 1;
+        sketch.increment(3);
+        assert_eq!(sketch.additions, sketch.sample_size / 2);
+        assert_eq!(sketch.frequency(1), MAX_COUNT / 2);
+        assert_eq!(sketch.frequency(2), 0);
+        assert_eq!(sketch.frequency(3), 0);
+    }
+}
+// endregion: --- Tests
@@ -1,3 +1,6 @@
+#[cfg(feature = "serde")]
+mod serde;
+
 use super::ExtNode as Node;
 use super::{Error, Result};
 use std::cell::RefCell;
@@ -22,10 +25,7 @@ where
     }
 }
 
-impl<T> Double<T>
-where
-    T: Debug + PartialEq + Clone,
-{
+impl<T> Double<T> {
     /// Create a new instance of the `Double` linked list.
     pub fn new() -> Self {
         Double {
@@ -34,6 +34,12 @@ where
             len: 0,
         }
     }
+}
+
+impl<T> Double<T>
+where
+    T: Debug + PartialEq + Clone,
+{
     /// Append a new value to the end of the list.
     pub fn append(&mut self, value: T) {
         let new_node = Rc::new(RefCell::new(Node::new(value)));
@@ -273,6 +279,82 @@ where
     }
 }
 
+/// Node-level operations, used by structures that keep handles to the nodes
+/// of a list to move or unlink them in O(1).
+impl<T> Double<T> {
+    /// Append a new value to the end of the list and return its node.
+    pub(crate) fn push_back_node(&mut self, value: T) -> Rc<RefCell<Node<T>>> {
+        let node = Rc::new(RefCell::new(Node::new(value)));
+        self.link_back(&node);
+        node
+    }
+    /// Get the number of nodes in the list.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+    /// Get the first node of the list.
+    pub(crate) fn front_node(&self) -> Option<Rc<RefCell<Node<T>>>> {
+        self.head.clone()
+    }
+    /// Unlink a node of this list, leaving its neighbours pointing at each other.
+    pub(crate) fn unlink_node(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        let previous = node
+            .borrow_mut()
+            .get_previous_mut()
+            .take()
+            .and_then(|previous| previous.upgrade());
+        let next = node.borrow_mut().get_next_mut().take();
+        match &previous {
+            Some(previous) => previous.borrow_mut().set_next(next.clone()),
+            None => self.head = next.clone(),
+        }
+        match &next {
+            Some(next) => next
+                .borrow_mut()
+                .set_previous(previous.as_ref().map(Rc::downgrade)),
+            None => self.tail = previous,
+        }
+        self.len -= 1;
+    }
+    /// Move a node of this list to its end.
+    pub(crate) fn move_node_to_back(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        if self
+            .tail
+            .as_ref()
+            .is_some_and(|tail| Rc::ptr_eq(tail, node))
+        {
+            return;
+        }
+        self.unlink_node(node);
+        self.link_back(node);
+    }
+    /// Link a detached node at the end of the list, such as one just
+    /// unlinked from another list.
+    pub(crate) fn link_back(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        match self.tail.take() {
+            Some(tail) => {
+                node.borrow_mut().set_previous(Some(Rc::downgrade(&tail)));
+                tail.borrow_mut().set_next(Some(node.clone()));
+            }
+            None => self.head = Some(node.clone()),
+        }
+        self.tail = Some(node.clone());
+        self.len += 1;
+    }
+}
+
+/// Unlink the nodes one by one, so dropping a long list doesn't recurse
+/// through every `next` pointer.
+impl<T> Drop for Double<T> {
+    fn drop(&mut self) {
+        self.tail = None;
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            next = node.borrow_mut().get_next_mut().take();
+        }
+    }
+}
+
 // region:    --- Tests
 
 #[cfg(test)]
@@ -298,6 +380,31 @@ mod test {
         list2.print();
     }
 
+    #[test]
+    fn test_double_linked_list_nodes() {
+        let mut list = Double::new();
+        let first = list.push_back_node(1);
+        let second = list.push_back_node(2);
+        let third = list.push_back_node(3);
+        list.move_node_to_back(&first);
+        assert_eq!(list.get(0).unwrap().unwrap(), 2);
+        assert_eq!(list.get(2).unwrap().unwrap(), 1);
+        list.unlink_node(&third);
+        assert_eq!(list.len, 2);
+        assert_eq!(list.get(1).unwrap().unwrap(), 1);
+        list.unlink_node(&second);
+        assert!(Rc::ptr_eq(&list.front_node().unwrap(), &first));
+        list.unlink_node(&first);
+        assert!(list.is_empty());
+        assert!(list.front_node().is_none());
+
+        let mut long = Double::new();
+        for i in 0..200_000 {
+            long.push_back_node(i);
+        }
+        drop(long);
+    }
+
     #[test]
     fn test_double_linked_list_errors() {
         let mut list: Double<i32> = Double::new();
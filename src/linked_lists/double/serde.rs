@@ -0,0 +1,71 @@
+use super::Double;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt::{Formatter, Result as FmtResult};
+use std::marker::PhantomData;
+
+/// Serialize a `Double` list as a sequence of its values, from head to tail.
+impl<T: Serialize> Serialize for Double<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            let node = node.borrow();
+            seq.serialize_element(node.get_value())?;
+            current = node.get_next().clone();
+        }
+        seq.end()
+    }
+}
+
+/// Deserialize a `Double` list from a sequence, keeping its order.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Double<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(DoubleVisitor(PhantomData))
+    }
+}
+
+struct DoubleVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for DoubleVisitor<T> {
+    type Value = Double<T>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut list = Double::new();
+        while let Some(value) = access.next_element()? {
+            list.push_back_node(value);
+        }
+        Ok(list)
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_serde() {
+        let list = Double::from_vec(vec![1, 2, 3]);
+        assert_eq!(serde_json::to_string(&list).unwrap(), "[1,2,3]");
+
+        let mut list: Double<String> = serde_json::from_str(r#"["a", "b", "c"]"#).unwrap();
+        assert_eq!(list.len, 3);
+        let tail = list.tail.clone().unwrap();
+        assert_eq!(tail.borrow().get_value(), "c");
+        let previous = tail.borrow().get_previous().clone().unwrap();
+        assert_eq!(previous.upgrade().unwrap().borrow().get_value(), "b");
+        assert_eq!(list.pop().unwrap(), Some("c".to_string()));
+        list.append("d".to_string());
+        assert_eq!(serde_json::to_string(&list).unwrap(), r#"["a","b","d"]"#);
+
+        let empty: Double<i32> = serde_json::from_str("[]").unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.head.is_none() && empty.tail.is_none());
+    }
+}
+// endregion: --- Tests
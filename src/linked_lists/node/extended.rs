@@ -2,7 +2,6 @@
 //!
 //!  `ExtNode` is a node in an extended linked list, such as a double one, containing a custom-type value and a pointer to the next and the previous node.
 use std::cell::RefCell;
-use std::fmt::Debug;
 use std::rc::{Rc, Weak};
 
 #[derive(Debug)]
@@ -12,10 +11,7 @@ pub struct ExtNode<T> {
     previous: Option<Weak<RefCell<ExtNode<T>>>>,
 }
 
-impl<T> ExtNode<T>
-where
-    T: Debug + PartialEq,
-{
+impl<T> ExtNode<T> {
     pub fn new(value: T) -> Self {
         ExtNode {
             value,
@@ -84,7 +80,7 @@ mod tests {
         let next_node = Rc::new(RefCell::new(ExtNode::new(5)));
         let middle_node = head_node.get_next_mut().as_ref().unwrap();
         middle_node.borrow_mut().set_next(Some(next_node));
-        let cloned_middle_node = Some(Rc::downgrade(&middle_node));
+        let cloned_middle_node = Some(Rc::downgrade(middle_node));
         let mut borrowed_middle_node = middle_node.borrow_mut();
         assert_eq!(*borrowed_middle_node.get_value(), 3);
         let last_node = borrowed_middle_node.get_next_mut().as_ref().unwrap();
@@ -1,17 +1,13 @@
 //! Node for singly linked list.
 //!
 //!  `SNode` is a node in the linear linked list, containing a custom-type value and a pointer to the next node.
-use std::fmt::Debug;
 #[derive(Debug, Clone)]
 pub struct SNode<T> {
     value: T,
     next: Option<Box<SNode<T>>>,
 }
 
-impl<T> SNode<T>
-where
-    T: Debug + PartialEq,
-{
+impl<T> SNode<T> {
     pub fn new(value: T) -> Self {
         SNode { value, next: None }
     }
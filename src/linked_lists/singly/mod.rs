@@ -1,3 +1,6 @@
+#[cfg(feature = "serde")]
+mod serde;
+
 use super::SNode as Node;
 use super::{Error, Result};
 use std::fmt::Debug;
@@ -205,9 +208,9 @@ mod test {
         list.append(3);
         list.append(4);
         list.append(5);
-        assert_eq!(list.is_empty(), false);
-        assert_eq!(list.search(3).unwrap(), true);
-        assert_eq!(list.update(3, 6).unwrap(), true);
+        assert!(!list.is_empty());
+        assert!(list.search(3).unwrap());
+        assert!(list.update(3, 6).unwrap());
         let list2 = Singly::from_vec(vec!["hello", "world", "rust"]);
         assert_eq!(list.pop().unwrap().unwrap(), 5);
         assert_eq!(list.pop().unwrap().unwrap(), 4);
@@ -0,0 +1,75 @@
+use super::{Node, Singly};
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt::{Formatter, Result as FmtResult};
+use std::marker::PhantomData;
+
+/// Serialize a `Singly` list as a sequence of its values, from head to end.
+impl<T: Serialize> Serialize for Singly<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        let mut current = &self.head;
+        while let Some(node) = current {
+            seq.serialize_element(node.get_value())?;
+            current = node.get_next();
+        }
+        seq.end()
+    }
+}
+
+/// Deserialize a `Singly` list from a sequence, keeping its order.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Singly<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(SinglyVisitor(PhantomData))
+    }
+}
+
+struct SinglyVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SinglyVisitor<T> {
+    type Value = Singly<T>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = access.next_element()? {
+            values.push(value);
+        }
+        // Link from the end, so each value is pushed in front in O(1).
+        let len = values.len();
+        let mut head = None;
+        for value in values.into_iter().rev() {
+            let mut node = Box::new(Node::new(value));
+            node.set_next(head);
+            head = Some(node);
+        }
+        Ok(Singly { head, len })
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_singly_serde() {
+        let list = Singly::from_vec(vec![1, 2, 3]);
+        assert_eq!(serde_json::to_string(&list).unwrap(), "[1,2,3]");
+
+        let mut list: Singly<String> = serde_json::from_str(r#"["a", "b", "c"]"#).unwrap();
+        assert_eq!(list.len, 3);
+        assert_eq!(list.get(2).unwrap(), Some(&"c".to_string()));
+        assert_eq!(list.pop().unwrap(), Some("c".to_string()));
+        list.append("d".to_string());
+        assert_eq!(serde_json::to_string(&list).unwrap(), r#"["a","b","d"]"#);
+
+        let empty: Singly<i32> = serde_json::from_str("[]").unwrap();
+        assert!(empty.is_empty());
+        assert!(serde_json::from_str::<Singly<i32>>(r#"{"a": 1}"#).is_err());
+    }
+}
+// endregion: --- Tests